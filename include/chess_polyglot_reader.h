@@ -0,0 +1,115 @@
+#ifndef CHESS_POLYGLOT_READER_H
+#define CHESS_POLYGLOT_READER_H
+
+/* Generated with cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * An open book.
+ */
+typedef struct PolyglotBook PolyglotBook;
+
+/**
+ * The entries found by a probe.
+ */
+typedef struct PolyglotProbe PolyglotProbe;
+
+/**
+ * A book entry with the move in Polyglot's 16-bit encoding.
+ */
+typedef struct PolyglotRawEntry {
+  uint16_t mv;
+  uint16_t weight;
+  uint32_t learn;
+} PolyglotRawEntry;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Opens the book at `path`. Returns `NULL` if it can't be opened.
+ *
+ * # Safety
+ * `path` must be `NULL` or a valid NUL-terminated string.
+ */
+struct PolyglotBook *polyglot_book_open(const char *path);
+
+/**
+ * Closes a book opened with `polyglot_book_open`. Does nothing if `book` is `NULL`.
+ *
+ * # Safety
+ * `book` must be `NULL` or a book returned by `polyglot_book_open` that hasn't been freed.
+ */
+void polyglot_book_free(struct PolyglotBook *book);
+
+/**
+ * The number of entries in the book.
+ *
+ * # Safety
+ * `book` must be `NULL` or a valid book.
+ */
+size_t polyglot_book_len(const struct PolyglotBook *book);
+
+/**
+ * Looks up the entries for a Polyglot hash. Returns `NULL` on failure.
+ *
+ * # Safety
+ * `book` must be `NULL` or a valid book.
+ */
+struct PolyglotProbe *polyglot_book_probe_hash(struct PolyglotBook *book, uint64_t hash);
+
+/**
+ * Looks up the entries for the position given as a FEN. Returns `NULL` on failure or if the FEN is invalid.
+ *
+ * # Safety
+ * `book` must be `NULL` or a valid book, and `fen` must be `NULL` or a valid NUL-terminated string.
+ */
+struct PolyglotProbe *polyglot_book_probe_fen(struct PolyglotBook *book,
+                                              const char *fen);
+
+/**
+ * Writes the Polyglot hash of the position given as a FEN to `hash`. Returns `false` if the FEN is invalid.
+ *
+ * # Safety
+ * `fen` must be `NULL` or a valid NUL-terminated string, and `hash` must be `NULL` or valid for writes.
+ */
+bool polyglot_hash_fen(const char *fen,
+                       uint64_t *hash);
+
+/**
+ * The number of entries found by a probe.
+ *
+ * # Safety
+ * `probe` must be `NULL` or a valid probe.
+ */
+size_t polyglot_probe_len(const struct PolyglotProbe *probe);
+
+/**
+ * Writes the entry at `index` to `entry`. Returns `false` if `index` is out of range.
+ *
+ * # Safety
+ * `probe` must be `NULL` or a valid probe, and `entry` must be `NULL` or valid for writes.
+ */
+bool polyglot_probe_get(const struct PolyglotProbe *probe,
+                        size_t index,
+                        struct PolyglotRawEntry *entry);
+
+/**
+ * Frees a probe result. Does nothing if `probe` is `NULL`.
+ *
+ * # Safety
+ * `probe` must be `NULL` or a probe that hasn't been freed.
+ */
+void polyglot_probe_free(struct PolyglotProbe *probe);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CHESS_POLYGLOT_READER_H */
@@ -0,0 +1,36 @@
+use chess_polyglot_reader::stats::{self, BookStats, StatsReader};
+use chess_polyglot_reader::{PolyglotKey, PolyglotReader};
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind};
+use std::path::Path;
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Prints the book moves for a position together with their sidecar stats.
+/// Usage: `book-stats <book.bin> [FEN]`, where the FEN defaults to the start position.
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.len() > 2 {
+        eprintln!("Usage: book-stats <book.bin> [FEN]");
+        std::process::exit(2);
+    }
+    if let Err(error) = run(Path::new(&args[0]), args.get(1).map_or(START_FEN, String::as_str)) {
+        eprintln!("book-stats: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn run(book: &Path, fen: &str) -> Result<(), Error> {
+    let key = fen.parse::<PolyglotKey>()
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, format!("{}: {}", error, fen)))?;
+    let reader = PolyglotReader::new(BufReader::new(File::open(book)?))?;
+    let stats = StatsReader::new(BufReader::new(File::open(stats::sidecar_path(book))?))?;
+    let entries = BookStats::new(reader, stats).get(&key)?;
+    if entries.is_empty() {
+        println!("No book moves");
+    }
+    for entry in entries {
+        println!("{}", entry);
+    }
+    Ok(())
+}
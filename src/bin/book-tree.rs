@@ -0,0 +1,57 @@
+use chess_polyglot_reader::board::Board;
+use chess_polyglot_reader::graph::{BookGraph, DotOptions};
+use chess_polyglot_reader::PolyglotReader;
+use std::fs::File;
+use std::io::{BufReader, Error, ErrorKind, Write};
+
+const USAGE: &str = "Usage: book-tree <book.bin> [--depth N] [--eco] [FEN]";
+
+/// Writes the book's tree below a position as Graphviz DOT to standard output.
+/// `--depth` limits how many plies are drawn (default 4), and `--eco` labels positions with their opening.
+fn main() {
+    let mut book = None;
+    let mut fen = None;
+    let mut depth = 4;
+    let mut eco_labels = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--eco" => eco_labels = true,
+            "--depth" => match args.next().and_then(|depth| depth.parse().ok()) {
+                Some(value) => depth = value,
+                None => usage()
+            },
+            _ if book.is_none() => book = Some(arg),
+            _ if fen.is_none() => fen = Some(arg),
+            _ => usage()
+        }
+    }
+    let book = book.unwrap_or_else(|| usage());
+    if let Err(error) = run(&book, fen.as_deref(), depth, eco_labels) {
+        eprintln!("book-tree: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+fn run(book: &str, fen: Option<&str>, depth: usize, eco_labels: bool) -> Result<(), Error> {
+    let root = match fen {
+        Some(fen) => fen.parse::<Board>()
+            .map_err(|error| Error::new(ErrorKind::InvalidInput, format!("{}: {}", error, fen)))?,
+        None => Board::default()
+    };
+    let mut reader = PolyglotReader::new(BufReader::new(File::open(book)?))?;
+    let graph = BookGraph::build(&mut reader, &[root], Some(depth))?;
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    graph.write_dot(&mut out, &DotOptions {
+        root: root.polyglot_hash_with(reader.table()),
+        max_depth: depth,
+        eco_labels
+    })?;
+    out.flush()
+}
@@ -0,0 +1,569 @@
+//! A board and legal move generator, for following book moves without an external chess library.
+
+use std::str::FromStr;
+
+use crate::fen::ParseFenError;
+use crate::keys::KeyTable;
+use crate::{CastleRights, Move, Piece, PieceType, PolyglotKey, Side, Square};
+
+const KNIGHT_OFFSETS: [(isize, isize); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_OFFSETS: [(isize, isize); 8] = [(1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1), (0, -1), (1, -1)];
+const DIAGONALS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const STRAIGHTS: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const PROMOTIONS: [PieceType; 4] = [PieceType::Queen, PieceType::Rook, PieceType::Bishop, PieceType::Knight];
+
+/// A full position: pieces, side to move, castling rights, en passant square and move counters.
+/// As in Polyglot, the en passant square is only kept if a pawn can actually capture en passant.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Board {
+    squares: [Option<(Side, PieceType)>; 64],
+    turn: Side,
+    white_castle: CastleRights,
+    black_castle: CastleRights,
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse().unwrap()
+    }
+}
+
+fn offset(square: Square, rank: isize, file: isize) -> Option<Square> {
+    let rank = square.rank() as isize + rank;
+    let file = square.file() as isize + file;
+    if (0..8).contains(&rank) && (0..8).contains(&file) {
+        Square::new(rank as usize, file as usize)
+    } else {
+        None
+    }
+}
+
+fn forward(side: Side) -> isize {
+    match side {
+        Side::White => 1,
+        Side::Black => -1
+    }
+}
+
+fn home_rank(side: Side) -> usize {
+    match side {
+        Side::White => 0,
+        Side::Black => 7
+    }
+}
+
+impl Board {
+    /// Builds a board from a key, with the move counters at their initial values.
+    /// Returns `None` if either side doesn't have exactly one king, a pawn is on its first or last rank,
+    /// the side not to move is in check, the key has variant state, or the en passant square isn't empty
+    /// with the pawn that just moved two squares beyond it.
+    pub fn from_key(key: &PolyglotKey) -> Option<Board> {
+        if key.variant.is_some() {
+            return None;
+        }
+        let mut squares = [None; 64];
+        for piece in &key.pieces {
+            if piece.piece_type == PieceType::Pawn && (piece.square.rank() == 0 || piece.square.rank() == 7) {
+                return None;
+            }
+            squares[piece.square.index()] = Some((piece.side, piece.piece_type));
+        }
+        let board = Board {
+            squares,
+            turn: key.turn,
+            white_castle: key.white_castle,
+            black_castle: key.black_castle,
+            en_passant: key.en_passant_file.map(|file| {
+                Square::new(if key.turn == Side::White { 5 } else { 2 }, file).unwrap()
+            }),
+            halfmove_clock: 0,
+            fullmove_number: 1
+        };
+        if let Some(en_passant) = board.en_passant {
+            let pushed = offset(en_passant, -forward(key.turn), 0).unwrap();
+            let origin = offset(en_passant, forward(key.turn), 0).unwrap();
+            if squares[en_passant.index()].is_some() || squares[origin.index()].is_some() ||
+                squares[pushed.index()] != Some((key.turn.opponent(), PieceType::Pawn)) {
+                return None;
+            }
+        }
+        for side in [Side::White, Side::Black] {
+            let kings = squares.iter().filter(|&&piece| piece == Some((side, PieceType::King))).count();
+            if kings != 1 {
+                return None;
+            }
+        }
+        if board.attacked(board.king(key.turn.opponent()), key.turn) {
+            return None;
+        }
+        Some(board)
+    }
+
+    pub fn key(&self) -> PolyglotKey {
+        PolyglotKey {
+            pieces: self.pieces().collect(),
+            white_castle: self.white_castle,
+            black_castle: self.black_castle,
+            en_passant_file: self.en_passant.map(Square::file),
+            turn: self.turn,
+            variant: None
+        }
+    }
+
+    pub fn polyglot_hash(&self) -> u64 {
+        self.key().polyglot_hash()
+    }
+
+    pub fn polyglot_hash_with(&self, table: &KeyTable) -> u64 {
+        self.key().polyglot_hash_with(table)
+    }
+
+    pub fn pieces(&self) -> impl Iterator<Item = Piece> + '_ {
+        (0..64).filter_map(move |index| self.piece_on(Square::from_index(index).unwrap()))
+    }
+
+    pub fn piece_on(&self, square: Square) -> Option<Piece> {
+        self.squares[square.index()].map(|(side, piece_type)| Piece {
+            piece_type,
+            side,
+            square
+        })
+    }
+
+    pub fn turn(&self) -> Side {
+        self.turn
+    }
+
+    pub fn castle_rights(&self, side: Side) -> CastleRights {
+        match side {
+            Side::White => self.white_castle,
+            Side::Black => self.black_castle
+        }
+    }
+
+    pub fn en_passant(&self) -> Option<Square> {
+        self.en_passant
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn fullmove_number(&self) -> u32 {
+        self.fullmove_number
+    }
+
+    fn king(&self, side: Side) -> Square {
+        let index = self.squares.iter().position(|&piece| piece == Some((side, PieceType::King))).unwrap();
+        Square::from_index(index).unwrap()
+    }
+
+    fn side_on(&self, square: Square) -> Option<Side> {
+        self.squares[square.index()].map(|(side, _)| side)
+    }
+
+    /// Whether a piece of `side` attacks `square`.
+    pub fn attacked(&self, square: Square, side: Side) -> bool {
+        let is = |square: Option<Square>, piece_types: &[PieceType]| {
+            square.and_then(|square| self.squares[square.index()])
+                .is_some_and(|(owner, piece_type)| owner == side && piece_types.contains(&piece_type))
+        };
+        if [-1, 1].iter().any(|&file| is(offset(square, -forward(side), file), &[PieceType::Pawn])) {
+            return true;
+        }
+        if KNIGHT_OFFSETS.iter().any(|&(rank, file)| is(offset(square, rank, file), &[PieceType::Knight])) {
+            return true;
+        }
+        if KING_OFFSETS.iter().any(|&(rank, file)| is(offset(square, rank, file), &[PieceType::King])) {
+            return true;
+        }
+        let rays = [
+            (&DIAGONALS, [PieceType::Bishop, PieceType::Queen]),
+            (&STRAIGHTS, [PieceType::Rook, PieceType::Queen])
+        ];
+        for (directions, sliders) in rays {
+            for &(rank, file) in directions {
+                let mut current = offset(square, rank, file);
+                while let Some(target) = current {
+                    if self.squares[target.index()].is_some() {
+                        if is(Some(target), &sliders) {
+                            return true;
+                        }
+                        break;
+                    }
+                    current = offset(target, rank, file);
+                }
+            }
+        }
+        false
+    }
+
+    pub fn in_check(&self) -> bool {
+        self.attacked(self.king(self.turn), self.turn.opponent())
+    }
+
+    /// Every legal move, with castling written as the king moving two squares.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.pseudo_legal_moves(&mut moves);
+        moves.retain(|&mv| {
+            let after = self.apply(mv);
+            !after.attacked(after.king(self.turn), after.turn)
+        });
+        moves
+    }
+
+    fn pseudo_legal_moves(&self, moves: &mut Vec<Move>) {
+        let turn = self.turn;
+        for source in (0..64).map(|index| Square::from_index(index).unwrap()) {
+            let piece_type = match self.squares[source.index()] {
+                Some((side, piece_type)) if side == turn => piece_type,
+                _ => continue
+            };
+            let add = |dest: Square| {
+                if self.side_on(dest) != Some(turn) {
+                    moves.push(Move::new(source, dest, None).unwrap());
+                }
+            };
+            match piece_type {
+                PieceType::Pawn => self.pawn_moves(source, moves),
+                PieceType::Knight => KNIGHT_OFFSETS.iter()
+                    .filter_map(|&(rank, file)| offset(source, rank, file))
+                    .for_each(add),
+                PieceType::King => {
+                    KING_OFFSETS.iter()
+                        .filter_map(|&(rank, file)| offset(source, rank, file))
+                        .for_each(add);
+                    self.castling_moves(source, moves);
+                }
+                PieceType::Bishop => self.sliding_moves(source, &DIAGONALS, moves),
+                PieceType::Rook => self.sliding_moves(source, &STRAIGHTS, moves),
+                PieceType::Queen => {
+                    self.sliding_moves(source, &DIAGONALS, moves);
+                    self.sliding_moves(source, &STRAIGHTS, moves);
+                }
+            }
+        }
+    }
+
+    fn pawn_moves(&self, source: Square, moves: &mut Vec<Move>) {
+        let turn = self.turn;
+        let mut add = |dest: Square| {
+            if dest.rank() == home_rank(turn.opponent()) {
+                for promotion in PROMOTIONS {
+                    moves.push(Move::new(source, dest, Some(promotion)).unwrap());
+                }
+            } else {
+                moves.push(Move::new(source, dest, None).unwrap());
+            }
+        };
+        if let Some(dest) = offset(source, forward(turn), 0).filter(|&dest| self.squares[dest.index()].is_none()) {
+            add(dest);
+            let start_rank = if turn == Side::White { 1 } else { 6 };
+            if source.rank() == start_rank {
+                if let Some(dest) = offset(dest, forward(turn), 0).filter(|&dest| self.squares[dest.index()].is_none()) {
+                    add(dest);
+                }
+            }
+        }
+        for file in [-1, 1] {
+            if let Some(dest) = offset(source, forward(turn), file) {
+                if self.side_on(dest) == Some(turn.opponent()) || self.en_passant == Some(dest) {
+                    add(dest);
+                }
+            }
+        }
+    }
+
+    fn sliding_moves(&self, source: Square, directions: &[(isize, isize)], moves: &mut Vec<Move>) {
+        for &(rank, file) in directions {
+            let mut current = offset(source, rank, file);
+            while let Some(dest) = current {
+                match self.side_on(dest) {
+                    Some(side) => {
+                        if side != self.turn {
+                            moves.push(Move::new(source, dest, None).unwrap());
+                        }
+                        break;
+                    }
+                    None => moves.push(Move::new(source, dest, None).unwrap())
+                }
+                current = offset(dest, rank, file);
+            }
+        }
+    }
+
+    fn castling_moves(&self, source: Square, moves: &mut Vec<Move>) {
+        let turn = self.turn;
+        let rank = home_rank(turn);
+        if source != Square::new(rank, 4).unwrap() || self.in_check() {
+            return;
+        }
+        let rights = self.castle_rights(turn);
+        let sides = [
+            (rights.king_side, 7, &[5, 6][..], &[5, 6][..]),
+            (rights.queen_side, 0, &[1, 2, 3][..], &[2, 3][..])
+        ];
+        for (allowed, rook_file, empty, safe) in sides {
+            let square = |file: usize| Square::new(rank, file).unwrap();
+            if allowed &&
+                self.squares[square(rook_file).index()] == Some((turn, PieceType::Rook)) &&
+                empty.iter().all(|&file| self.squares[square(file).index()].is_none()) &&
+                safe.iter().all(|&file| !self.attacked(square(file), turn.opponent())) {
+                let dest = if rook_file == 7 { 6 } else { 2 };
+                moves.push(Move::new(source, square(dest), None).unwrap());
+            }
+        }
+    }
+
+    /// Plays a move that is at least pseudo-legal, with castling written as the king moving two squares.
+    fn apply(&self, mv: Move) -> Board {
+        let mut board = *self;
+        let (source, dest) = (mv.source(), mv.dest());
+        let (side, piece_type) = self.squares[source.index()].unwrap();
+        let capture = self.squares[dest.index()].is_some();
+        board.squares[source.index()] = None;
+        board.squares[dest.index()] = Some((side, mv.promotion().unwrap_or(piece_type)));
+
+        if piece_type == PieceType::Pawn && source.file() != dest.file() && !capture {
+            board.squares[Square::new(source.rank(), dest.file()).unwrap().index()] = None;
+        }
+        if piece_type == PieceType::King && source.file() == 4 && dest.file().abs_diff(4) == 2 {
+            let (rook_source, rook_dest) = if dest.file() == 6 { (7, 5) } else { (0, 3) };
+            board.squares[Square::new(source.rank(), rook_source).unwrap().index()] = None;
+            board.squares[Square::new(source.rank(), rook_dest).unwrap().index()] = Some((side, PieceType::Rook));
+        }
+
+        if piece_type == PieceType::King {
+            let rights = match side {
+                Side::White => &mut board.white_castle,
+                Side::Black => &mut board.black_castle
+            };
+            rights.king_side = false;
+            rights.queen_side = false;
+        }
+        for square in [source, dest] {
+            match (square.rank(), square.file()) {
+                (0, 0) => board.white_castle.queen_side = false,
+                (0, 7) => board.white_castle.king_side = false,
+                (7, 0) => board.black_castle.queen_side = false,
+                (7, 7) => board.black_castle.king_side = false,
+                _ => {}
+            }
+        }
+
+        board.en_passant = None;
+        if piece_type == PieceType::Pawn && source.rank().abs_diff(dest.rank()) == 2 {
+            let capturable = [-1, 1].iter().any(|&file| {
+                offset(dest, 0, file).is_some_and(|square| {
+                    self.squares[square.index()] == Some((side.opponent(), PieceType::Pawn))
+                })
+            });
+            if capturable {
+                board.en_passant = Square::new((source.rank() + dest.rank()) / 2, source.file());
+            }
+        }
+        board.halfmove_clock = if piece_type == PieceType::Pawn || capture {
+            0
+        } else {
+            self.halfmove_clock + 1
+        };
+        if side == Side::Black {
+            board.fullmove_number += 1;
+        }
+        board.turn = side.opponent();
+        board
+    }
+
+    /// Converts Polyglot castling (king takes own rook) into the king moving two squares.
+    fn standard_castling(&self, mv: Move) -> Move {
+        let (source, dest) = (mv.source(), mv.dest());
+        let rank = home_rank(self.turn);
+        if source == Square::new(rank, 4).unwrap() &&
+            dest.rank() == rank &&
+            (dest.file() == 0 || dest.file() == 7) &&
+            self.squares[source.index()] == Some((self.turn, PieceType::King)) &&
+            self.squares[dest.index()] == Some((self.turn, PieceType::Rook)) {
+            Move::new(source, Square::new(rank, if dest.file() == 7 { 6 } else { 2 }).unwrap(), None).unwrap()
+        } else {
+            mv
+        }
+    }
+
+    /// Whether the move is legal. Castling may be written either as the king moving two squares or taking its rook.
+    pub fn is_legal(&self, mv: Move) -> bool {
+        self.legal_moves().contains(&self.standard_castling(mv))
+    }
+
+    /// The position after a move, or `None` if it's illegal.
+    /// Castling may be written either as the king moving two squares or taking its rook.
+    pub fn play(&self, mv: Move) -> Option<Board> {
+        let mv = self.standard_castling(mv);
+        if self.legal_moves().contains(&mv) {
+            Some(self.apply(mv))
+        } else {
+            None
+        }
+    }
+
+    /// Finds the legal move written in Standard Algebraic Notation, with castling as the king moving two squares.
+    /// Check and annotation suffixes are ignored, castling may be written with zeros,
+    /// and the `=` before a promotion is optional. Returns `None` if no single legal move matches.
+    pub fn parse_san(&self, san: &str) -> Option<Move> {
+        let san = san.trim_end_matches(['+', '#', '!', '?']);
+        let castle = match san {
+            "O-O" | "0-0" => Some(6),
+            "O-O-O" | "0-0-0" => Some(2),
+            _ => None
+        };
+        let moves = self.legal_moves();
+        if let Some(file) = castle {
+            let king = self.king(self.turn);
+            return moves.into_iter().find(|mv| {
+                mv.source() == king && king.file() == 4 && mv.dest().file() == file && mv.dest().rank() == king.rank()
+            });
+        }
+
+        let (piece_type, rest) = match san.chars().next()? {
+            c @ ('N' | 'B' | 'R' | 'Q' | 'K') => (PieceType::from_char(c)?, &san[1..]),
+            _ => (PieceType::Pawn, san)
+        };
+        let (rest, promotion) = match rest.char_indices().last()? {
+            (i, c @ ('N' | 'B' | 'R' | 'Q')) if piece_type == PieceType::Pawn => {
+                (rest[..i].trim_end_matches('='), Some(PieceType::from_char(c)?))
+            }
+            _ => (rest, None)
+        };
+        if rest.len() < 2 || !rest.is_char_boundary(rest.len() - 2) {
+            return None;
+        }
+        let dest: Square = rest[rest.len() - 2..].parse().ok()?;
+        let mut source_file = None;
+        let mut source_rank = None;
+        for c in rest[..rest.len() - 2].chars() {
+            match c {
+                'a'..='h' => source_file = Some(c as usize - 'a' as usize),
+                '1'..='8' => source_rank = Some(c as usize - '1' as usize),
+                'x' => {}
+                _ => return None
+            }
+        }
+
+        let mut candidates = moves.into_iter().filter(|mv| {
+            mv.dest() == dest &&
+                mv.promotion() == promotion &&
+                self.squares[mv.source().index()].is_some_and(|(_, source_type)| source_type == piece_type) &&
+                source_file.is_none_or(|file| mv.source().file() == file) &&
+                source_rank.is_none_or(|rank| mv.source().rank() == rank)
+        });
+        let mv = candidates.next()?;
+        if candidates.next().is_some() {
+            return None;
+        }
+        Some(mv)
+    }
+
+    /// The number of leaf positions of the legal move tree `depth` plies deep.
+    pub fn perft(&self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        let moves = self.legal_moves();
+        if depth == 1 {
+            return moves.len() as u64;
+        }
+        moves.into_iter().map(|mv| self.apply(mv).perft(depth - 1)).sum()
+    }
+
+    /// The colour-mirrored position: ranks flipped, colours, castling rights and turn swapped.
+    pub fn mirrored(&self) -> Board {
+        let mut board = *self;
+        for index in 0..64 {
+            let square = Square::from_index(index).unwrap();
+            board.squares[square.mirrored().index()] = self.squares[index].map(|(side, piece_type)| {
+                (side.opponent(), piece_type)
+            });
+        }
+        board.white_castle = self.black_castle;
+        board.black_castle = self.white_castle;
+        board.en_passant = self.en_passant.map(Square::mirrored);
+        board.turn = self.turn.opponent();
+        board
+    }
+}
+
+/// Parses a FEN as `PolyglotKey` does, along with the optional move counters.
+/// Fails if the position isn't one `Board::from_key` accepts.
+impl FromStr for Board {
+    type Err = ParseFenError;
+
+    fn from_str(fen: &str) -> Result<Board, ParseFenError> {
+        let key: PolyglotKey = fen.parse()?;
+        let mut board = Board::from_key(&key).ok_or(ParseFenError)?;
+        let mut counters = fen.split_whitespace().skip(4);
+        if let Some(halfmove_clock) = counters.next() {
+            board.halfmove_clock = halfmove_clock.parse().map_err(|_| ParseFenError)?;
+        }
+        if let Some(fullmove_number) = counters.next() {
+            board.fullmove_number = fullmove_number.parse().map_err(|_| ParseFenError)?;
+        }
+        Ok(board)
+    }
+}
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.squares[rank * 8 + file] {
+                    Some((side, piece_type)) => {
+                        if empty > 0 {
+                            write!(f, "{}", empty)?;
+                            empty = 0;
+                        }
+                        let c = piece_type.to_char();
+                        write!(f, "{}", if side == Side::White { c.to_ascii_uppercase() } else { c })?;
+                    }
+                    None => empty += 1
+                }
+            }
+            if empty > 0 {
+                write!(f, "{}", empty)?;
+            }
+            if rank > 0 {
+                f.write_str("/")?;
+            }
+        }
+        f.write_str(if self.turn == Side::White { " w " } else { " b " })?;
+        let castling: String = [
+            (self.white_castle.king_side, 'K'),
+            (self.white_castle.queen_side, 'Q'),
+            (self.black_castle.king_side, 'k'),
+            (self.black_castle.queen_side, 'q')
+        ].iter().filter(|(allowed, _)| *allowed).map(|(_, c)| *c).collect();
+        f.write_str(if castling.is_empty() { "-" } else { &castling })?;
+        match self.en_passant {
+            Some(square) => write!(f, " {}", square)?,
+            None => f.write_str(" -")?
+        }
+        write!(f, " {} {}", self.halfmove_clock, self.fullmove_number)
+    }
+}
+
+#[cfg(feature = "chess_lib_helpers")]
+impl From<&chess::Board> for Board {
+    fn from(board: &chess::Board) -> Board {
+        Board::from_key(&PolyglotKey::from_board(board)).expect("chess::Board is always a valid position")
+    }
+}
+
+#[cfg(feature = "chess_lib_helpers")]
+impl From<&Board> for chess::Board {
+    fn from(board: &Board) -> chess::Board {
+        board.to_string().parse().expect("Board is always a valid position")
+    }
+}
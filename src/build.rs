@@ -0,0 +1,285 @@
+//! Building books from PGN files on a pool of worker threads.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::sync_channel;
+use std::sync::Mutex;
+
+use crate::board::Board;
+use crate::filter::GameFilter;
+use crate::keys::KeyTable;
+use crate::pgn::{GameResult, PgnGame, PgnReader};
+use crate::stats::StatsWriter;
+use crate::{sort_entries, Move, PolyglotEntry, PolyglotWriter, Side};
+
+const SHARDS: usize = 64;
+/// The number of games handed to a worker at a time.
+const CHUNK_GAMES: usize = 256;
+
+#[derive(Debug, Clone)]
+pub struct BuildOptions {
+    /// Only the first this many plies of each game are added.
+    pub max_plies: Option<usize>,
+    /// Moves played in fewer games than this are left out.
+    pub min_games: u32,
+    /// The number of worker threads, or 0 for one per core.
+    pub threads: usize,
+    /// Which games are added, and which of their moves.
+    pub filter: GameFilter,
+    /// The table positions are hashed with.
+    pub table: KeyTable
+}
+
+impl Default for BuildOptions {
+    fn default() -> Self {
+        Self {
+            max_plies: None,
+            min_games: 1,
+            threads: 0,
+            filter: GameFilter::default(),
+            table: KeyTable::STANDARD
+        }
+    }
+}
+
+/// How a move did, from the point of view of the side that played it.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct MoveStats {
+    pub games: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32
+}
+
+impl MoveStats {
+    fn add(&mut self, other: &MoveStats) {
+        self.games = self.games.saturating_add(other.games);
+        self.wins = self.wins.saturating_add(other.wins);
+        self.draws = self.draws.saturating_add(other.draws);
+        self.losses = self.losses.saturating_add(other.losses);
+    }
+
+    /// The weight Polyglot's own book builder uses: two points per win and one per draw.
+    pub fn weight(&self) -> u64 {
+        2 * self.wins as u64 + self.draws as u64
+    }
+
+    /// The percentage of points scored in finished games, counting draws as half a point.
+    pub fn score(&self) -> Option<f64> {
+        let finished = self.wins as u64 + self.draws as u64 + self.losses as u64;
+        if finished == 0 {
+            return None;
+        }
+        Some(self.weight() as f64 * 50.0 / finished as f64)
+    }
+}
+
+impl std::fmt::Display for MoveStats {
+    /// Formats as `3 games, +1 =1 -1, 50.0%`, leaving out the score if no game finished.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} game{}, +{} ={} -{}",
+            self.games,
+            if self.games == 1 { "" } else { "s" },
+            self.wins,
+            self.draws,
+            self.losses
+        )?;
+        match self.score() {
+            Some(score) => write!(f, ", {:.1}%", score),
+            None => Ok(())
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct BuildSummary {
+    pub games: usize,
+    /// Games with a move that couldn't be parsed or was illegal. None of their moves are added.
+    pub skipped_games: usize,
+    /// Games rejected by the filter.
+    pub filtered_games: usize,
+    pub positions: usize,
+    pub entries: usize
+}
+
+type Stats = HashMap<(u64, Move), MoveStats>;
+
+/// Collected statistics for a move played from the position with the given key.
+pub type KeyMoveStats = (u64, Move, MoveStats);
+
+/// The book moves of a game with the side that played each of them.
+fn game_moves(game: &PgnGame, max_plies: Option<usize>, table: &KeyTable) -> Option<Vec<(u64, Move, Side)>> {
+    let mut board = match game.tag("FEN") {
+        Some(fen) => fen.parse().ok()?,
+        None => Board::default()
+    };
+    let plies = max_plies.map_or(game.moves.len(), |max_plies| max_plies.min(game.moves.len()));
+    let mut moves = Vec::with_capacity(plies);
+    for san in &game.moves[..plies] {
+        let mv = board.parse_san(san)?;
+        let key = board.key();
+        moves.push((key.polyglot_hash_with(table), mv.from_standard_castling(&key), board.turn()));
+        board = board.play(mv)?;
+    }
+    Some(moves)
+}
+
+fn add_game(stats: &mut Stats, moves: &[(u64, Move, Side)], result: Option<GameResult>, filter: &GameFilter) {
+    for &(key, mv, side) in moves {
+        if !filter.result.includes(result, side) {
+            continue;
+        }
+        stats.entry((key, mv)).or_default().add(&MoveStats {
+            games: 1,
+            wins: matches!((result, side), (Some(GameResult::WhiteWins), Side::White) | (Some(GameResult::BlackWins), Side::Black)) as u32,
+            draws: (result == Some(GameResult::Draw)) as u32,
+            losses: matches!((result, side), (Some(GameResult::WhiteWins), Side::Black) | (Some(GameResult::BlackWins), Side::White)) as u32
+        });
+    }
+}
+
+/// Replays every game in `paths` and collects statistics for each (key, move) pair, sorted by key and move.
+/// Files are read in order on the calling thread, and their games are shared out in chunks between worker threads,
+/// which replay and hash them. The result doesn't depend on how many workers there are. Counts saturate at `u32::MAX`.
+pub fn collect_stats(
+    paths: &[PathBuf],
+    options: &BuildOptions
+) -> Result<(Vec<KeyMoveStats>, BuildSummary), std::io::Error> {
+    let threads = match options.threads {
+        0 => std::thread::available_parallelism().map_or(1, |threads| threads.get()),
+        threads => threads
+    };
+    let shards: Vec<Mutex<Stats>> = (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect();
+    let summary = Mutex::new(BuildSummary::default());
+    let (sender, receiver) = sync_channel::<Vec<PgnGame>>(threads * 2);
+    let receiver = Mutex::new(receiver);
+
+    let worker = || {
+        loop {
+            // The lock is only held while waiting for the next chunk, not while replaying it.
+            let games = match receiver.lock().unwrap().recv() {
+                Ok(games) => games,
+                Err(_) => break
+            };
+            let mut stats = Stats::new();
+            let mut chunk_summary = BuildSummary::default();
+            for game in games {
+                if !options.filter.accepts(&game) {
+                    chunk_summary.filtered_games += 1;
+                    continue;
+                }
+                match game_moves(&game, options.max_plies, &options.table) {
+                    Some(moves) => {
+                        add_game(&mut stats, &moves, game.result, &options.filter);
+                        chunk_summary.games += 1;
+                    }
+                    None => chunk_summary.skipped_games += 1
+                }
+            }
+
+            let mut by_shard: Vec<Vec<_>> = (0..SHARDS).map(|_| Vec::new()).collect();
+            for (key_move, move_stats) in stats {
+                by_shard[(key_move.0 % SHARDS as u64) as usize].push((key_move, move_stats));
+            }
+            for (shard, entries) in shards.iter().zip(by_shard) {
+                let mut shard = shard.lock().unwrap();
+                for (key_move, move_stats) in entries {
+                    shard.entry(key_move).or_default().add(&move_stats);
+                }
+            }
+            let mut summary = summary.lock().unwrap();
+            summary.games += chunk_summary.games;
+            summary.skipped_games += chunk_summary.skipped_games;
+            summary.filtered_games += chunk_summary.filtered_games;
+        }
+    };
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(worker);
+        }
+        let read = || -> Result<(), std::io::Error> {
+            let mut games = Vec::with_capacity(CHUNK_GAMES);
+            for path in paths {
+                for game in PgnReader::new(BufReader::new(File::open(path)?)) {
+                    games.push(game?);
+                    if games.len() == CHUNK_GAMES {
+                        sender.send(std::mem::replace(&mut games, Vec::with_capacity(CHUNK_GAMES))).unwrap();
+                    }
+                }
+            }
+            if !games.is_empty() {
+                sender.send(games).unwrap();
+            }
+            Ok(())
+        };
+        let result = read();
+        // Dropping the sender lets the workers finish once the queue is empty, even after an error.
+        drop(sender);
+        result
+    })?;
+
+    let mut stats: Vec<_> = shards.into_iter()
+        .flat_map(|shard| shard.into_inner().unwrap())
+        .filter(|(_, move_stats)| move_stats.games >= options.min_games)
+        .map(|((key, mv), move_stats)| (key, mv, move_stats))
+        .collect();
+    stats.sort_unstable_by_key(|&(key, mv, _)| (key, mv.to_u16()));
+    let mut summary = summary.into_inner().unwrap();
+    summary.entries = stats.len();
+    summary.positions = stats.windows(2).filter(|pair| pair[0].0 != pair[1].0).count() + !stats.is_empty() as usize;
+    Ok((stats, summary))
+}
+
+/// Builds a book from the games in `paths`, weighting moves with `MoveStats::weight`.
+/// Where a position's weights don't fit in 16 bits, they are scaled down so the largest one does.
+pub fn build_book<W: Write>(
+    paths: &[PathBuf],
+    writer: &mut PolyglotWriter<W>,
+    options: &BuildOptions
+) -> Result<BuildSummary, std::io::Error> {
+    let (stats, summary) = collect_stats(paths, options)?;
+    write_stats(&stats, writer)?;
+    Ok(summary)
+}
+
+/// Writes collected statistics as a book, as `build_book` does.
+pub fn write_stats<W: Write>(
+    stats: &[KeyMoveStats],
+    writer: &mut PolyglotWriter<W>
+) -> Result<(), std::io::Error> {
+    for position in stats.chunk_by(|a, b| a.0 == b.0) {
+        let max_weight = position.iter().map(|(_, _, move_stats)| move_stats.weight()).max().unwrap_or(0);
+        let mut entries: Vec<_> = position.iter().map(|&(key, mv, move_stats)| {
+            let weight = if max_weight > u16::MAX as u64 {
+                move_stats.weight() * u16::MAX as u64 / max_weight
+            } else {
+                move_stats.weight()
+            };
+            (key, PolyglotEntry {
+                mv,
+                weight: weight as u16,
+                learn: 0
+            })
+        }).collect();
+        sort_entries(&mut entries);
+        for (key, entry) in &entries {
+            writer.write(*key, entry)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes collected statistics to a sidecar, so they can be read back alongside the book `write_stats` writes.
+pub fn write_sidecar<W: Write>(
+    stats: &[KeyMoveStats],
+    writer: &mut StatsWriter<W>
+) -> Result<(), std::io::Error> {
+    for (key, mv, move_stats) in stats {
+        writer.write(*key, *mv, move_stats)?;
+    }
+    Ok(())
+}
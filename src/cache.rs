@@ -0,0 +1,121 @@
+//! An LRU cache in front of a reader, for books whose early positions are probed over and over.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Seek};
+
+use crate::{PolyglotEntry, PolyglotKey, PolyglotReader};
+
+#[derive(Debug)]
+pub struct CachedReader<I> {
+    reader: PolyglotReader<I>,
+    capacity: usize,
+    /// The entries of each cached position and when it was last used.
+    cache: HashMap<u64, (Vec<PolyglotEntry>, u64)>,
+    /// Cached positions by when they were last used, oldest first.
+    recency: BTreeMap<u64, u64>,
+    tick: u64,
+    hits: u64,
+    misses: u64
+}
+
+impl<I: Seek + Read> CachedReader<I> {
+    /// Caches the lookups of up to `capacity` positions, including positions that aren't in the book.
+    pub fn new(reader: PolyglotReader<I>, capacity: usize) -> Self {
+        Self {
+            reader,
+            capacity,
+            cache: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0
+        }
+    }
+
+    pub fn get(&mut self, key: &PolyglotKey) -> Result<Vec<PolyglotEntry>, std::io::Error> {
+        self.get_hash(key.polyglot_hash_with(self.reader.table()))
+    }
+
+    pub fn get_hash(&mut self, hash: u64) -> Result<Vec<PolyglotEntry>, std::io::Error> {
+        self.tick += 1;
+        if let Some((entries, last_used)) = self.cache.get_mut(&hash) {
+            self.recency.remove(last_used);
+            self.recency.insert(self.tick, hash);
+            *last_used = self.tick;
+            self.hits += 1;
+            return Ok(entries.clone());
+        }
+        self.misses += 1;
+        let entries = self.reader.get_hash(hash)?;
+        self.insert(hash, entries.clone());
+        Ok(entries)
+    }
+
+    fn insert(&mut self, hash: u64, entries: Vec<PolyglotEntry>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some((_, last_used)) = self.cache.remove(&hash) {
+            self.recency.remove(&last_used);
+        }
+        while self.cache.len() >= self.capacity {
+            let (_, oldest) = self.recency.pop_first().unwrap();
+            self.cache.remove(&oldest);
+        }
+        self.cache.insert(hash, (entries, self.tick));
+        self.recency.insert(self.tick, hash);
+    }
+
+    /// Loads every position within `max_plies` book moves of the start position into the cache,
+    /// as far as the capacity allows. The shallowest positions are marked as the most recently used.
+    /// Returns the number of positions loaded. Doesn't count towards the hits and misses.
+    pub fn prewarm(&mut self, max_plies: usize) -> Result<usize, std::io::Error> {
+        let lines = crate::walk::reachable_lines(&mut self.reader, &[crate::board::Board::default()], Some(max_plies))?;
+        let mut positions: Vec<_> = lines.iter().map(|(&hash, line)| (line.ply(), hash)).collect();
+        positions.sort_unstable_by(|a, b| b.cmp(a));
+        let skipped = positions.len().saturating_sub(self.capacity);
+        for &(_, hash) in &positions[skipped..] {
+            let entries = self.reader.get_hash(hash)?;
+            self.tick += 1;
+            self.insert(hash, entries);
+        }
+        Ok(positions.len() - skipped)
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of cached positions.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// Empties the cache and resets the hit and miss counters.
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.recency.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    pub fn reader(&mut self) -> &mut PolyglotReader<I> {
+        &mut self.reader
+    }
+
+    pub fn into_inner(self) -> PolyglotReader<I> {
+        self.reader
+    }
+}
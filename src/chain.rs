@@ -0,0 +1,92 @@
+//! Looking positions up in several books at once, such as a narrow curated book backed by a broad one.
+
+use std::io::{Read, Seek};
+
+use crate::{PolyglotEntry, PolyglotKey, PolyglotReader};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum ChainMode {
+    /// Answers from the first book, in priority order, that has the position.
+    #[default]
+    Fallback,
+    /// Answers with the moves of every book that has the position, with weights scaled per book.
+    /// Weights of a move found in several books are added together.
+    Merge
+}
+
+/// A book entry and the index of the book it came from.
+/// When merging, this is the highest priority book that has the move.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ChainedEntry {
+    pub entry: PolyglotEntry,
+    pub book: usize
+}
+
+#[derive(Debug)]
+pub struct BookChain<I> {
+    pub mode: ChainMode,
+    books: Vec<(PolyglotReader<I>, f64)>
+}
+
+impl<I: Seek + Read> BookChain<I> {
+    pub fn new(mode: ChainMode) -> Self {
+        Self {
+            mode,
+            books: Vec::new()
+        }
+    }
+
+    /// Adds a book with a lower priority than the books already in the chain.
+    /// Its weights are multiplied by `weight_scale` when merging, and saturate at `u16::MAX`.
+    pub fn push(&mut self, reader: PolyglotReader<I>, weight_scale: f64) {
+        self.books.push((reader, weight_scale));
+    }
+
+    pub fn book(&mut self, index: usize) -> Option<&mut PolyglotReader<I>> {
+        self.books.get_mut(index).map(|(reader, _)| reader)
+    }
+
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+
+    /// Looks the position up in each book with that book's key table.
+    pub fn get(&mut self, key: &PolyglotKey) -> Result<Vec<ChainedEntry>, std::io::Error> {
+        self.lookup(|reader| reader.get(key))
+    }
+
+    /// In fallback mode, entries keep the order of their book.
+    /// When merging, entries are sorted by weight in descending order.
+    pub fn get_hash(&mut self, hash: u64) -> Result<Vec<ChainedEntry>, std::io::Error> {
+        self.lookup(|reader| reader.get_hash(hash))
+    }
+
+    fn lookup<F: FnMut(&mut PolyglotReader<I>) -> Result<Vec<PolyglotEntry>, std::io::Error>>(
+        &mut self,
+        mut get: F
+    ) -> Result<Vec<ChainedEntry>, std::io::Error> {
+        let mut result: Vec<ChainedEntry> = Vec::new();
+        for (book, (reader, weight_scale)) in self.books.iter_mut().enumerate() {
+            let entries = get(reader)?;
+            if self.mode == ChainMode::Fallback {
+                if !entries.is_empty() {
+                    return Ok(entries.into_iter().map(|entry| ChainedEntry { entry, book }).collect());
+                }
+                continue;
+            }
+            for mut entry in entries {
+                entry.weight = (entry.weight as f64 * *weight_scale).round().clamp(0.0, u16::MAX as f64) as u16;
+                match result.iter_mut().find(|chained| chained.entry.mv == entry.mv) {
+                    Some(chained) => chained.entry.weight = chained.entry.weight.saturating_add(entry.weight),
+                    None => result.push(ChainedEntry { entry, book })
+                }
+            }
+        }
+        result.sort_by_key(|chained| std::cmp::Reverse(chained.entry.weight));
+        Ok(result)
+    }
+}
@@ -0,0 +1,107 @@
+//! Finding book entries stored under a key that collides with a reachable position.
+//!
+//! A move that is illegal in the position its key was reached from most likely belongs to another position
+//! with the same 64-bit key, so it is reported as suspicious.
+
+use std::io::{Read, Seek, Write};
+
+use crate::board::Board;
+use crate::{PolyglotEntry, PolyglotReader, PolyglotWriter};
+
+#[derive(Debug, Clone)]
+pub struct CollisionOptions {
+    pub roots: Vec<Board>,
+    /// Positions further than this many plies from every root aren't checked.
+    pub max_plies: Option<usize>
+}
+
+impl Default for CollisionOptions {
+    fn default() -> Self {
+        Self {
+            roots: vec![Board::default()],
+            max_plies: None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SuspiciousEntry {
+    pub key: u64,
+    pub entry: PolyglotEntry,
+    /// The reachable position the key was computed from, in which the move is illegal.
+    pub fen: String
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct CollisionReport {
+    pub checked_positions: usize,
+    pub checked_entries: usize,
+    /// Positions that aren't reachable from the roots can't be reconstructed, so they aren't checked.
+    pub unchecked_positions: usize,
+    /// Sorted by key, with a key's entries in book order.
+    pub suspicious: Vec<SuspiciousEntry>
+}
+
+impl CollisionReport {
+    pub fn is_clean(&self) -> bool {
+        self.suspicious.is_empty()
+    }
+
+    fn is_suspicious(&self, key: u64, entry: &PolyglotEntry) -> bool {
+        let start = self.suspicious.partition_point(|suspicious| suspicious.key < key);
+        self.suspicious[start..].iter()
+            .take_while(|suspicious| suspicious.key == key)
+            .any(|suspicious| suspicious.entry == *entry)
+    }
+}
+
+/// Reconstructs every position reachable from the roots, recomputes its key,
+/// and checks that each of the book's moves for that key is legal in it.
+pub fn check_collisions<I: Seek + Read>(
+    reader: &mut PolyglotReader<I>,
+    options: &CollisionOptions
+) -> Result<CollisionReport, std::io::Error> {
+    let lines = crate::walk::reachable_lines(reader, &options.roots, options.max_plies)?;
+
+    let mut report = CollisionReport::default();
+    for position in reader.positions()? {
+        let (key, entries) = position?;
+        let line = match lines.get(&key) {
+            Some(line) => line,
+            None => {
+                report.unchecked_positions += 1;
+                continue;
+            }
+        };
+        report.checked_positions += 1;
+        report.checked_entries += entries.len();
+        for entry in entries {
+            if !line.board.is_legal(entry.mv) {
+                report.suspicious.push(SuspiciousEntry {
+                    key,
+                    entry,
+                    fen: line.fen()
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// Copies the book to `writer` without the entries flagged in `report`. Returns the number of entries removed.
+pub fn strip_suspicious<I: Seek + Read, W: Write>(
+    reader: &mut PolyglotReader<I>,
+    writer: &mut PolyglotWriter<W>,
+    report: &CollisionReport
+) -> Result<usize, std::io::Error> {
+    let mut removed = 0;
+    for entry in reader.entries()? {
+        let (key, entry) = entry?;
+        if report.is_suspicious(key, &entry) {
+            removed += 1;
+        } else {
+            writer.write(key, &entry)?;
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,133 @@
+use std::io::{Read, Seek};
+
+use crate::{Move, PolyglotEntry, PolyglotReader};
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct DiffOptions {
+    /// Weight changes are only reported if the weights differ by more than this.
+    pub weight_threshold: u16
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WeightChange {
+    pub key: u64,
+    pub mv: Move,
+    pub old: u16,
+    pub new: u16
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BookDiff {
+    pub added_positions: Vec<(u64, Vec<PolyglotEntry>)>,
+    pub removed_positions: Vec<(u64, Vec<PolyglotEntry>)>,
+    pub added_moves: Vec<(u64, PolyglotEntry)>,
+    pub removed_moves: Vec<(u64, PolyglotEntry)>,
+    pub weight_changes: Vec<WeightChange>
+}
+
+impl BookDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_positions.is_empty() &&
+            self.removed_positions.is_empty() &&
+            self.added_moves.is_empty() &&
+            self.removed_moves.is_empty() &&
+            self.weight_changes.is_empty()
+    }
+
+    /// Every key mentioned by the diff, in ascending order.
+    pub fn keys(&self) -> Vec<u64> {
+        let mut keys: Vec<_> = self.added_positions.iter().map(|(key, _)| *key)
+            .chain(self.removed_positions.iter().map(|(key, _)| *key))
+            .chain(self.added_moves.iter().map(|(key, _)| *key))
+            .chain(self.removed_moves.iter().map(|(key, _)| *key))
+            .chain(self.weight_changes.iter().map(|change| change.key))
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        keys
+    }
+
+    /// Resolves the keys of the diff to lines from the start position.
+    /// Removed positions are looked up in the old book and everything else in the new one.
+    /// Keys that aren't reachable from the start position are left out.
+    pub fn resolve_lines<A: Seek + Read, B: Seek + Read>(
+        &self,
+        old: &mut PolyglotReader<A>,
+        new: &mut PolyglotReader<B>
+    ) -> Result<std::collections::HashMap<u64, crate::walk::BookLine>, std::io::Error> {
+        let roots = [crate::board::Board::default()];
+        let mut lines = crate::walk::reachable_lines(old, &roots, None)?;
+        lines.extend(crate::walk::reachable_lines(new, &roots, None)?);
+        let keys = self.keys();
+        lines.retain(|key, _| keys.binary_search(key).is_ok());
+        Ok(lines)
+    }
+}
+
+/// Compares two books, walking both in lockstep. Both books must be sorted by key.
+pub fn diff<A: Seek + Read, B: Seek + Read>(
+    old: &mut PolyglotReader<A>,
+    new: &mut PolyglotReader<B>,
+    options: &DiffOptions
+) -> Result<BookDiff, std::io::Error> {
+    let mut diff = BookDiff::default();
+    let mut old = old.positions()?;
+    let mut new = new.positions()?;
+    let mut old_position = old.next().transpose()?;
+    let mut new_position = new.next().transpose()?;
+    loop {
+        match (old_position.take(), new_position.take()) {
+            (None, None) => break,
+            (Some(position), None) => {
+                diff.removed_positions.push(position);
+                old_position = old.next().transpose()?;
+            }
+            (None, Some(position)) => {
+                diff.added_positions.push(position);
+                new_position = new.next().transpose()?;
+            }
+            (Some(old_pos), Some(new_pos)) => {
+                if old_pos.0 < new_pos.0 {
+                    diff.removed_positions.push(old_pos);
+                    old_position = old.next().transpose()?;
+                    new_position = Some(new_pos);
+                } else if old_pos.0 > new_pos.0 {
+                    diff.added_positions.push(new_pos);
+                    new_position = new.next().transpose()?;
+                    old_position = Some(old_pos);
+                } else {
+                    diff_position(&mut diff, old_pos.0, old_pos.1, new_pos.1, options);
+                    old_position = old.next().transpose()?;
+                    new_position = new.next().transpose()?;
+                }
+            }
+        }
+    }
+    Ok(diff)
+}
+
+fn diff_position(
+    diff: &mut BookDiff,
+    key: u64,
+    old: Vec<PolyglotEntry>,
+    mut new: Vec<PolyglotEntry>,
+    options: &DiffOptions
+) {
+    for old_entry in old {
+        match new.iter().position(|new_entry| new_entry.mv == old_entry.mv) {
+            Some(index) => {
+                let new_entry = new.remove(index);
+                if old_entry.weight.abs_diff(new_entry.weight) > options.weight_threshold {
+                    diff.weight_changes.push(WeightChange {
+                        key,
+                        mv: old_entry.mv,
+                        old: old_entry.weight,
+                        new: new_entry.weight
+                    });
+                }
+            }
+            None => diff.removed_moves.push((key, old_entry))
+        }
+    }
+    diff.added_moves.extend(new.into_iter().map(|entry| (key, entry)));
+}
@@ -0,0 +1,49 @@
+//! ECO codes and opening names for common book positions.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::PolyglotKey;
+
+/// Tab-separated rows of ECO code, opening name, moves in SAN and the FEN of the position they reach.
+const TABLE: &str = include_str!("eco.tsv");
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Opening {
+    pub eco: &'static str,
+    pub name: &'static str,
+    /// The moves in SAN from the start position that the table lists for this opening.
+    pub moves: &'static str
+}
+
+impl std::fmt::Display for Opening {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.eco, self.name)
+    }
+}
+
+fn openings() -> &'static HashMap<u64, Opening> {
+    static OPENINGS: OnceLock<HashMap<u64, Opening>> = OnceLock::new();
+    OPENINGS.get_or_init(|| {
+        TABLE.lines().map(|row| {
+            let fields: Vec<_> = row.split('\t').collect();
+            let key: PolyglotKey = fields[3].parse().expect("Invalid FEN in ECO table");
+            (key.polyglot_hash(), Opening {
+                eco: fields[0],
+                name: fields[1],
+                moves: fields[2]
+            })
+        }).collect()
+    })
+}
+
+/// The opening of the position with the given Polyglot hash.
+/// Positions are matched exactly, so transpositions are classified but positions past the table's lines aren't.
+pub fn classify(hash: u64) -> Option<Opening> {
+    openings().get(&hash).copied()
+}
+
+/// The opening of the last classified position in a line, given as the hashes of its positions in order.
+pub fn classify_line(hashes: &[u64]) -> Option<Opening> {
+    hashes.iter().rev().find_map(|&hash| classify(hash))
+}
@@ -0,0 +1,172 @@
+use std::str::FromStr;
+
+use crate::{CastleRights, Piece, PieceType, PolyglotKey, Side, Square};
+use crate::variant::{Pockets, VariantState};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseFenError;
+
+impl std::fmt::Display for ParseFenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid FEN")
+    }
+}
+
+impl std::error::Error for ParseFenError {}
+
+/// Parses the first four fields of a FEN. The move counters are optional and ignored.
+/// As in Polyglot, the en passant file is only kept if a pawn can actually capture en passant.
+///
+/// Crazyhouse pockets may follow the placement in brackets, as in `RNBQKBNR[Qp]`, and promoted pieces may be marked
+/// with a `~` after them. Markers are rejected outside Crazyhouse.
+/// Three-check counts may be given as checks remaining (`3+2`) or, after the counters, as checks given (`+0+1`).
+impl FromStr for PolyglotKey {
+    type Err = ParseFenError;
+
+    fn from_str(fen: &str) -> Result<PolyglotKey, ParseFenError> {
+        let mut fields = fen.split_whitespace();
+        let placement = fields.next().ok_or(ParseFenError)?;
+        let turn = fields.next().ok_or(ParseFenError)?;
+        let castling = fields.next().ok_or(ParseFenError)?;
+        let en_passant = fields.next().ok_or(ParseFenError)?;
+
+        let (placement, pockets) = match placement.split_once('[') {
+            Some((placement, pockets)) => {
+                (placement, Some(parse_pockets(pockets.strip_suffix(']').ok_or(ParseFenError)?)?))
+            }
+            None => (placement, None)
+        };
+        let mut pieces = Vec::new();
+        let mut promoted = 0u64;
+        let ranks: Vec<_> = placement.split('/').collect();
+        if ranks.len() != 8 {
+            return Err(ParseFenError);
+        }
+        for (i, rank) in ranks.iter().enumerate() {
+            let mut file = 0;
+            let mut last_piece = None;
+            for c in rank.chars() {
+                if c == '~' {
+                    let square: Square = last_piece.take().ok_or(ParseFenError)?;
+                    promoted |= 1 << square.index();
+                } else if let Some(empty) = c.to_digit(10) {
+                    last_piece = None;
+                    file += empty as usize;
+                } else {
+                    let square = Square::new(7 - i, file).ok_or(ParseFenError)?;
+                    pieces.push(Piece {
+                        piece_type: PieceType::from_char(c).ok_or(ParseFenError)?,
+                        side: if c.is_ascii_uppercase() { Side::White } else { Side::Black },
+                        square
+                    });
+                    last_piece = Some(square);
+                    file += 1;
+                }
+            }
+            if file != 8 {
+                return Err(ParseFenError);
+            }
+        }
+
+        let mut variant = match pockets {
+            Some(pockets) => Some(VariantState::Crazyhouse { pockets, promoted }),
+            None if promoted != 0 => return Err(ParseFenError),
+            None => None
+        };
+        for field in fields {
+            if field.contains('+') {
+                if variant.is_some() {
+                    return Err(ParseFenError);
+                }
+                variant = Some(parse_checks(field)?);
+            }
+        }
+
+        let turn = match turn {
+            "w" => Side::White,
+            "b" => Side::Black,
+            _ => return Err(ParseFenError)
+        };
+
+        let mut white_castle = CastleRights {
+            queen_side: false,
+            king_side: false
+        };
+        let mut black_castle = white_castle;
+        if castling != "-" {
+            for c in castling.chars() {
+                match c {
+                    'K' => white_castle.king_side = true,
+                    'Q' => white_castle.queen_side = true,
+                    'k' => black_castle.king_side = true,
+                    'q' => black_castle.queen_side = true,
+                    _ => return Err(ParseFenError)
+                }
+            }
+        }
+
+        let en_passant_file = if en_passant == "-" {
+            None
+        } else {
+            let square: Square = en_passant.parse().map_err(|_| ParseFenError)?;
+            let capturer_rank = match turn {
+                Side::White if square.rank() == 5 => 4,
+                Side::Black if square.rank() == 2 => 3,
+                _ => return Err(ParseFenError)
+            };
+            let can_capture = pieces.iter().any(|piece| {
+                piece.piece_type == PieceType::Pawn &&
+                    piece.side == turn &&
+                    piece.square.rank() == capturer_rank &&
+                    piece.square.file().abs_diff(square.file()) == 1
+            });
+            if can_capture {
+                Some(square.file())
+            } else {
+                None
+            }
+        };
+
+        Ok(PolyglotKey {
+            pieces,
+            white_castle,
+            black_castle,
+            en_passant_file,
+            turn,
+            variant
+        })
+    }
+}
+
+fn parse_pockets(pockets: &str) -> Result<Pockets, ParseFenError> {
+    let mut parsed = Pockets::default();
+    for c in pockets.chars().filter(|&c| c != '-') {
+        let side = if c.is_ascii_uppercase() { Side::White } else { Side::Black };
+        if !parsed.add(side, PieceType::from_char(c).ok_or(ParseFenError)?) {
+            return Err(ParseFenError);
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parses Three-check counts, either remaining (`3+2`) or given (`+0+1`), white first.
+fn parse_checks(field: &str) -> Result<VariantState, ParseFenError> {
+    let (given, counts) = match field.strip_prefix('+') {
+        Some(counts) => (true, counts),
+        None => (false, field)
+    };
+    let (white, black) = counts.split_once('+').ok_or(ParseFenError)?;
+    let parse = |count: &str| count.parse::<u8>().ok().filter(|&count| count <= 3).ok_or(ParseFenError);
+    let (white, black) = (parse(white)?, parse(black)?);
+    Ok(if given {
+        VariantState::ThreeCheck {
+            white_checks: white,
+            black_checks: black
+        }
+    } else {
+        VariantState::ThreeCheck {
+            white_checks: 3 - white,
+            black_checks: 3 - black
+        }
+    })
+}
@@ -0,0 +1,174 @@
+//! A C ABI for probing books. See `include/chess_polyglot_reader.h`.
+//!
+//! Books and probe results are opaque handles that must be released with their `_free` function.
+//! Functions return `NULL` (or `false`) on failure instead of reporting errors.
+
+use std::ffi::CStr;
+use std::fs::File;
+use std::io::BufReader;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+use crate::{PolyglotEntry, PolyglotKey, PolyglotReader};
+
+/// An open book.
+pub struct PolyglotBook {
+    reader: PolyglotReader<BufReader<File>>
+}
+
+/// The entries found by a probe.
+pub struct PolyglotProbe {
+    entries: Vec<PolyglotRawEntry>
+}
+
+/// A book entry with the move in Polyglot's 16-bit encoding.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PolyglotRawEntry {
+    pub mv: u16,
+    pub weight: u16,
+    pub learn: u32
+}
+
+impl From<&PolyglotEntry> for PolyglotRawEntry {
+    fn from(entry: &PolyglotEntry) -> Self {
+        Self {
+            mv: entry.mv.to_u16(),
+            weight: entry.weight,
+            learn: entry.learn
+        }
+    }
+}
+
+unsafe fn parse_fen(fen: *const c_char) -> Option<PolyglotKey> {
+    if fen.is_null() {
+        return None;
+    }
+    CStr::from_ptr(fen).to_str().ok()?.parse().ok()
+}
+
+fn probe(book: &mut PolyglotBook, hash: u64) -> *mut PolyglotProbe {
+    match book.reader.get_hash(hash) {
+        Ok(entries) => Box::into_raw(Box::new(PolyglotProbe {
+            entries: entries.iter().map(PolyglotRawEntry::from).collect()
+        })),
+        Err(_) => ptr::null_mut()
+    }
+}
+
+/// Opens the book at `path`. Returns `NULL` if it can't be opened.
+///
+/// # Safety
+/// `path` must be `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_book_open(path: *const c_char) -> *mut PolyglotBook {
+    if path.is_null() {
+        return ptr::null_mut();
+    }
+    let path = match CStr::from_ptr(path).to_str() {
+        Ok(path) => path,
+        Err(_) => return ptr::null_mut()
+    };
+    let reader = File::open(path).and_then(|file| PolyglotReader::new(BufReader::new(file)));
+    match reader {
+        Ok(reader) => Box::into_raw(Box::new(PolyglotBook { reader })),
+        Err(_) => ptr::null_mut()
+    }
+}
+
+/// Closes a book opened with `polyglot_book_open`. Does nothing if `book` is `NULL`.
+///
+/// # Safety
+/// `book` must be `NULL` or a book returned by `polyglot_book_open` that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_book_free(book: *mut PolyglotBook) {
+    if !book.is_null() {
+        drop(Box::from_raw(book));
+    }
+}
+
+/// The number of entries in the book.
+///
+/// # Safety
+/// `book` must be `NULL` or a valid book.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_book_len(book: *const PolyglotBook) -> usize {
+    book.as_ref().map_or(0, |book| book.reader.len())
+}
+
+/// Looks up the entries for a Polyglot hash. Returns `NULL` on failure.
+///
+/// # Safety
+/// `book` must be `NULL` or a valid book.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_book_probe_hash(book: *mut PolyglotBook, hash: u64) -> *mut PolyglotProbe {
+    match book.as_mut() {
+        Some(book) => catch_unwind(AssertUnwindSafe(|| probe(book, hash))).unwrap_or(ptr::null_mut()),
+        None => ptr::null_mut()
+    }
+}
+
+/// Looks up the entries for the position given as a FEN. Returns `NULL` on failure or if the FEN is invalid.
+///
+/// # Safety
+/// `book` must be `NULL` or a valid book, and `fen` must be `NULL` or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_book_probe_fen(book: *mut PolyglotBook, fen: *const c_char) -> *mut PolyglotProbe {
+    match (book.as_mut(), parse_fen(fen)) {
+        (Some(book), Some(key)) => {
+            catch_unwind(AssertUnwindSafe(|| probe(book, key.polyglot_hash()))).unwrap_or(ptr::null_mut())
+        }
+        _ => ptr::null_mut()
+    }
+}
+
+/// Writes the Polyglot hash of the position given as a FEN to `hash`. Returns `false` if the FEN is invalid.
+///
+/// # Safety
+/// `fen` must be `NULL` or a valid NUL-terminated string, and `hash` must be `NULL` or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_hash_fen(fen: *const c_char, hash: *mut u64) -> bool {
+    match (parse_fen(fen), hash.as_mut()) {
+        (Some(key), Some(hash)) => {
+            *hash = key.polyglot_hash();
+            true
+        }
+        _ => false
+    }
+}
+
+/// The number of entries found by a probe.
+///
+/// # Safety
+/// `probe` must be `NULL` or a valid probe.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_probe_len(probe: *const PolyglotProbe) -> usize {
+    probe.as_ref().map_or(0, |probe| probe.entries.len())
+}
+
+/// Writes the entry at `index` to `entry`. Returns `false` if `index` is out of range.
+///
+/// # Safety
+/// `probe` must be `NULL` or a valid probe, and `entry` must be `NULL` or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_probe_get(probe: *const PolyglotProbe, index: usize, entry: *mut PolyglotRawEntry) -> bool {
+    match (probe.as_ref().and_then(|probe| probe.entries.get(index)), entry.as_mut()) {
+        (Some(found), Some(entry)) => {
+            *entry = *found;
+            true
+        }
+        _ => false
+    }
+}
+
+/// Frees a probe result. Does nothing if `probe` is `NULL`.
+///
+/// # Safety
+/// `probe` must be `NULL` or a probe that hasn't been freed.
+#[no_mangle]
+pub unsafe extern "C" fn polyglot_probe_free(probe: *mut PolyglotProbe) {
+    if !probe.is_null() {
+        drop(Box::from_raw(probe));
+    }
+}
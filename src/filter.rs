@@ -0,0 +1,165 @@
+//! Choosing which PGN games, and which of their moves, go into a book.
+
+use std::str::FromStr;
+
+use crate::pgn::{GameResult, PgnGame};
+use crate::Side;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TimeControl {
+    Bullet,
+    Blitz,
+    Rapid,
+    Classical,
+    Correspondence
+}
+
+impl TimeControl {
+    /// Categorises a `TimeControl` tag such as `180+2` by the estimated game duration,
+    /// the base time plus 40 times the increment: under 3 minutes is bullet, under 8 blitz and under 25 rapid.
+    /// `-` is correspondence. Returns `None` for unknown (`?`), malformed or overflowing tags.
+    pub fn from_pgn(tag: &str) -> Option<TimeControl> {
+        if tag == "-" {
+            return Some(TimeControl::Correspondence);
+        }
+        // Multi-period controls like `40/7200:3600` are categorised by their first period.
+        let period = tag.split(':').next()?;
+        let (base, increment): (u64, u64) = if let Some(seconds) = period.strip_prefix('*') {
+            (seconds.parse().ok()?, 0)
+        } else if let Some((_, seconds)) = period.split_once('/') {
+            (seconds.parse().ok()?, 0)
+        } else if let Some((base, increment)) = period.split_once('+') {
+            (base.parse().ok()?, increment.parse().ok()?)
+        } else {
+            (period.parse().ok()?, 0)
+        };
+        Some(match increment.checked_mul(40)?.checked_add(base)? {
+            0..=179 => TimeControl::Bullet,
+            180..=479 => TimeControl::Blitz,
+            480..=1499 => TimeControl::Rapid,
+            _ => TimeControl::Classical
+        })
+    }
+}
+
+/// Which results games need and whose moves are taken from them.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum ResultFilter {
+    /// Every game, including unfinished ones.
+    #[default]
+    All,
+    /// Games with a result.
+    Finished,
+    /// Games that were won by either side.
+    Decisive,
+    /// Only the moves of the side that won.
+    Winner,
+    /// The moves of the side that won, and both sides' moves in draws.
+    NotLoser
+}
+
+impl ResultFilter {
+    /// Whether moves played by `side` in a game with `result` are kept.
+    pub fn includes(&self, result: Option<GameResult>, side: Side) -> bool {
+        match (self, result) {
+            (ResultFilter::All, _) => true,
+            (_, None) => false,
+            (ResultFilter::Finished, Some(_)) => true,
+            (ResultFilter::Decisive, Some(result)) => result != GameResult::Draw,
+            (ResultFilter::Winner, Some(result)) => result == GameResult::winner(side),
+            (ResultFilter::NotLoser, Some(result)) => result == GameResult::Draw || result == GameResult::winner(side)
+        }
+    }
+}
+
+/// A date as written in a PGN `Date` tag. Unknown months and days (`??`) are 0, so they sort first.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct PgnDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseDateError;
+
+impl std::fmt::Display for ParseDateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid date")
+    }
+}
+
+impl std::error::Error for ParseDateError {}
+
+impl FromStr for PgnDate {
+    type Err = ParseDateError;
+
+    /// Parses `YYYY.MM.DD`, where the month and day may be `??`. The year must be known.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(['.', '-']);
+        let year = parts.next().ok_or(ParseDateError)?.parse().map_err(|_| ParseDateError)?;
+        let mut component = || match parts.next() {
+            Some("??") | None => Ok(0),
+            Some(part) => part.parse().map_err(|_| ParseDateError)
+        };
+        let month = component()?;
+        let day = component()?;
+        if parts.next().is_some() || month > 12 || day > 31 {
+            return Err(ParseDateError);
+        }
+        Ok(Self {
+            year,
+            month,
+            day
+        })
+    }
+}
+
+/// Rules games must pass before they are added to a book. The default accepts every game.
+/// Games missing a tag a rule needs are rejected by that rule.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct GameFilter {
+    /// The minimum `WhiteElo` and `BlackElo`.
+    pub min_elo: Option<u16>,
+    /// The accepted time control categories. Empty accepts any time control, including none.
+    pub time_controls: Vec<TimeControl>,
+    pub result: ResultFilter,
+    /// The earliest `Date`, inclusive.
+    pub min_date: Option<PgnDate>,
+    /// The latest `Date`, inclusive.
+    pub max_date: Option<PgnDate>,
+    /// Games with fewer plies than this are rejected.
+    pub min_plies: usize
+}
+
+impl GameFilter {
+    /// Whether `game` passes every rule. The result rule only rejects games none of whose moves it would keep.
+    pub fn accepts(&self, game: &PgnGame) -> bool {
+        if game.moves.len() < self.min_plies {
+            return false;
+        }
+        if let Some(min_elo) = self.min_elo {
+            let elo = |tag| game.tag(tag).and_then(|elo| elo.parse::<u16>().ok());
+            if !matches!((elo("WhiteElo"), elo("BlackElo")), (Some(white), Some(black)) if white >= min_elo && black >= min_elo) {
+                return false;
+            }
+        }
+        if !self.time_controls.is_empty() {
+            match game.tag("TimeControl").and_then(TimeControl::from_pgn) {
+                Some(time_control) if self.time_controls.contains(&time_control) => {}
+                _ => return false
+            }
+        }
+        if self.min_date.is_some() || self.max_date.is_some() {
+            let date = match game.tag("Date").and_then(|date| date.parse::<PgnDate>().ok()) {
+                Some(date) => date,
+                None => return false
+            };
+            if self.min_date.is_some_and(|min_date| date < min_date)
+                || self.max_date.is_some_and(|max_date| date > max_date) {
+                return false;
+            }
+        }
+        self.result.includes(game.result, Side::White) || self.result.includes(game.result, Side::Black)
+    }
+}
@@ -0,0 +1,234 @@
+//! The graph of positions reachable through book moves, for finding transpositions and repetitions.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{Read, Seek, Write};
+
+use crate::board::Board;
+use crate::{eco, Move, PolyglotReader};
+
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub board: Board,
+    /// The fewest book moves needed to reach the position from a root.
+    pub ply: usize,
+    /// Indices into `BookGraph::edges`.
+    pub out_edges: Vec<usize>,
+    pub in_edges: Vec<usize>
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphEdge {
+    pub from: u64,
+    pub to: u64,
+    pub mv: Move,
+    pub san: String,
+    pub weight: u16
+}
+
+/// The positions reachable from a set of roots through book moves, and the moves between them.
+#[derive(Debug, Clone, Default)]
+pub struct BookGraph {
+    pub roots: Vec<u64>,
+    pub nodes: HashMap<u64, GraphNode>,
+    pub edges: Vec<GraphEdge>
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct DotOptions {
+    pub root: u64,
+    /// Only positions at most this many plies below the root are drawn.
+    pub max_depth: usize,
+    /// Adds the ECO code and opening name to the labels of positions found in `eco::classify`.
+    pub eco_labels: bool
+}
+
+impl BookGraph {
+    /// Builds the graph breadth-first from `roots`, not expanding positions at `max_plies` or deeper.
+    /// Book moves that are illegal in the reconstructed position are skipped.
+    pub fn build<I: Seek + Read>(
+        reader: &mut PolyglotReader<I>,
+        roots: &[Board],
+        max_plies: Option<usize>
+    ) -> Result<BookGraph, std::io::Error> {
+        let mut graph = BookGraph::default();
+        let mut queue = VecDeque::new();
+        for &board in roots {
+            let key = board.polyglot_hash_with(reader.table());
+            if !graph.nodes.contains_key(&key) {
+                graph.roots.push(key);
+                graph.add_node(key, board, 0);
+                queue.push_back(key);
+            }
+        }
+
+        while let Some(key) = queue.pop_front() {
+            let node = &graph.nodes[&key];
+            if max_plies.is_some_and(|max_plies| node.ply >= max_plies) {
+                continue;
+            }
+            let (board, ply) = (node.board, node.ply);
+            let position = board.key();
+            for entry in reader.get_hash(key)? {
+                let child = match board.play(entry.mv) {
+                    Some(child) => child,
+                    None => continue
+                };
+                let child_key = child.polyglot_hash_with(reader.table());
+                if !graph.nodes.contains_key(&child_key) {
+                    graph.add_node(child_key, child, ply + 1);
+                    queue.push_back(child_key);
+                }
+                let edge = graph.edges.len();
+                graph.edges.push(GraphEdge {
+                    from: key,
+                    to: child_key,
+                    mv: entry.mv,
+                    san: entry.mv.to_san(&position).unwrap_or_else(|| entry.mv.to_string()),
+                    weight: entry.weight
+                });
+                graph.nodes.get_mut(&key).unwrap().out_edges.push(edge);
+                graph.nodes.get_mut(&child_key).unwrap().in_edges.push(edge);
+            }
+        }
+        Ok(graph)
+    }
+
+    fn add_node(&mut self, key: u64, board: Board, ply: usize) {
+        self.nodes.insert(key, GraphNode {
+            board,
+            ply,
+            out_edges: Vec::new(),
+            in_edges: Vec::new()
+        });
+    }
+
+    pub fn in_degree(&self, key: u64) -> usize {
+        self.nodes.get(&key).map_or(0, |node| node.in_edges.len())
+    }
+
+    pub fn out_degree(&self, key: u64) -> usize {
+        self.nodes.get(&key).map_or(0, |node| node.out_edges.len())
+    }
+
+    /// Positions reached by more than one book move, with the number of moves reaching them,
+    /// sorted by that number in descending order.
+    pub fn transpositions(&self) -> Vec<(u64, usize)> {
+        let mut transpositions: Vec<_> = self.nodes.iter()
+            .map(|(&key, node)| (key, node.in_edges.len()))
+            .filter(|&(_, in_degree)| in_degree > 1)
+            .collect();
+        transpositions.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        transpositions
+    }
+
+    /// Groups of positions that can repeat by following book moves: every strongly connected component
+    /// with more than one position, or a single position with a move back to itself.
+    pub fn cycles(&self) -> Vec<Vec<u64>> {
+        let mut keys: Vec<_> = self.nodes.keys().copied().collect();
+        keys.sort_unstable();
+        let successors = |key: u64| self.nodes[&key].out_edges.iter().map(move |&edge| self.edges[edge].to);
+
+        // Iterative version of Tarjan's algorithm.
+        let mut index = HashMap::new();
+        let mut low_link = HashMap::new();
+        let mut stack = Vec::new();
+        let mut on_stack = HashSet::new();
+        let mut components = Vec::new();
+        for &start in &keys {
+            if index.contains_key(&start) {
+                continue;
+            }
+            let mut work = vec![(start, 0)];
+            while let Some((key, child)) = work.pop() {
+                if child == 0 {
+                    let next = index.len();
+                    index.insert(key, next);
+                    low_link.insert(key, next);
+                    stack.push(key);
+                    on_stack.insert(key);
+                }
+                if let Some(successor) = successors(key).nth(child) {
+                    work.push((key, child + 1));
+                    if !index.contains_key(&successor) {
+                        work.push((successor, 0));
+                    } else if on_stack.contains(&successor) {
+                        low_link.insert(key, low_link[&key].min(index[&successor]));
+                    }
+                    continue;
+                }
+                if let Some(&(parent, _)) = work.last() {
+                    low_link.insert(parent, low_link[&parent].min(low_link[&key]));
+                }
+                if low_link[&key] == index[&key] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack.remove(&member);
+                        component.push(member);
+                        if member == key {
+                            break;
+                        }
+                    }
+                    if component.len() > 1 || successors(key).any(|successor| successor == key) {
+                        component.sort_unstable();
+                        components.push(component);
+                    }
+                }
+            }
+        }
+        components
+    }
+
+    /// Writes the subtree below `options.root` in Graphviz DOT format.
+    /// Positions are labelled with their key, and optionally their opening, and edges with the move in SAN and its weight.
+    pub fn write_dot<W: Write>(&self, mut out: W, options: &DotOptions) -> Result<(), std::io::Error> {
+        let mut depths = HashMap::new();
+        let mut queue = VecDeque::new();
+        if self.nodes.contains_key(&options.root) {
+            depths.insert(options.root, 0);
+            queue.push_back(options.root);
+        }
+        let mut edges = Vec::new();
+        while let Some(key) = queue.pop_front() {
+            let depth = depths[&key];
+            if depth >= options.max_depth {
+                continue;
+            }
+            for &edge in &self.nodes[&key].out_edges {
+                let to = self.edges[edge].to;
+                if let Entry::Vacant(vacant) = depths.entry(to) {
+                    vacant.insert(depth + 1);
+                    queue.push_back(to);
+                }
+                edges.push(edge);
+            }
+        }
+
+        let mut keys: Vec<_> = depths.keys().copied().collect();
+        keys.sort_unstable_by_key(|key| (depths[key], *key));
+        writeln!(out, "digraph book {{")?;
+        for key in keys {
+            let opening = options.eco_labels.then(|| eco::classify(self.nodes[&key].board.polyglot_hash())).flatten();
+            writeln!(
+                out,
+                "    \"{:016x}\" [label=\"{:016x}{}\", tooltip=\"{}\"];",
+                key,
+                key,
+                opening.map(|opening| format!("\\n{}", opening)).unwrap_or_default(),
+                self.nodes[&key].board
+            )?;
+        }
+        for edge in edges {
+            let edge = &self.edges[edge];
+            writeln!(
+                out,
+                "    \"{:016x}\" -> \"{:016x}\" [label=\"{} ({})\"];",
+                edge.from,
+                edge.to,
+                edge.san,
+                edge.weight
+            )?;
+        }
+        writeln!(out, "}}")
+    }
+}
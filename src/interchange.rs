@@ -0,0 +1,211 @@
+//! Human-editable CSV and JSON Lines versions of a book, and rebuilding books from them.
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Read, Seek, Write};
+
+use crate::{sort_entries, Move, PolyglotEntry, PolyglotReader, PolyglotWriter};
+
+/// One book entry with its key, and the FEN of its position if known.
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BookRecord {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_hex"))]
+    pub key: u64,
+    #[cfg_attr(feature = "serde", serde(rename = "move"))]
+    pub mv: Move,
+    pub weight: u16,
+    pub learn: u32,
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub fen: Option<String>
+}
+
+impl BookRecord {
+    pub fn entry(&self) -> PolyglotEntry {
+        PolyglotEntry {
+            mv: self.mv,
+            weight: self.weight,
+            learn: self.learn
+        }
+    }
+}
+
+/// Parses a hash written as hex digits, with or without a `0x` prefix.
+pub fn parse_hash(hash: &str) -> Option<u64> {
+    let digits = hash.strip_prefix("0x").unwrap_or(hash);
+    if digits.is_empty() || digits.len() > 16 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/// The FEN of every position reachable from the start position through book moves.
+pub fn reachable_fens<I: Seek + Read>(reader: &mut PolyglotReader<I>) -> Result<HashMap<u64, String>, std::io::Error> {
+    let lines = crate::walk::reachable_lines(reader, &[crate::board::Board::default()], None)?;
+    Ok(lines.into_iter().map(|(key, line)| (key, line.fen())).collect())
+}
+
+fn records<'a, I: Seek + Read>(
+    reader: &'a mut PolyglotReader<I>,
+    fens: &'a HashMap<u64, String>
+) -> Result<impl Iterator<Item = Result<BookRecord, std::io::Error>> + 'a, std::io::Error> {
+    Ok(reader.entries()?.map(move |entry| {
+        let (key, entry) = entry?;
+        Ok(BookRecord {
+            key,
+            mv: entry.mv,
+            weight: entry.weight,
+            learn: entry.learn,
+            fen: fens.get(&key).cloned()
+        })
+    }))
+}
+
+/// Writes every entry of the book as a CSV row with the columns `key,move,weight,learn,fen`.
+/// The FEN column is filled in from `fens` where possible. Returns the number of rows written.
+pub fn export_csv<I: Seek + Read, W: Write>(
+    reader: &mut PolyglotReader<I>,
+    mut out: W,
+    fens: &HashMap<u64, String>
+) -> Result<usize, std::io::Error> {
+    writeln!(out, "key,move,weight,learn,fen")?;
+    let mut count = 0;
+    for record in records(reader, fens)? {
+        let record = record?;
+        writeln!(
+            out,
+            "{:016x},{},{},{},{}",
+            record.key,
+            record.mv,
+            record.weight,
+            record.learn,
+            record.fen.as_deref().unwrap_or("")
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Reads CSV rows as written by `export_csv`. Columns are matched by the header, so they may be reordered,
+/// and the `learn` and `fen` columns are optional.
+pub fn read_csv<R: BufRead>(input: R) -> Result<Vec<BookRecord>, std::io::Error> {
+    let mut lines = input.lines();
+    let header = match lines.next() {
+        Some(header) => header?,
+        None => return Ok(Vec::new())
+    };
+    let columns: Vec<_> = split_csv_row(&header).iter().map(|column| column.to_ascii_lowercase()).collect();
+    let column = |name: &str| columns.iter().position(|column| column == name);
+    let key_column = column("key").ok_or_else(|| invalid_data(1, "missing key column"))?;
+    let move_column = column("move").ok_or_else(|| invalid_data(1, "missing move column"))?;
+    let weight_column = column("weight").ok_or_else(|| invalid_data(1, "missing weight column"))?;
+    let learn_column = column("learn");
+    let fen_column = column("fen");
+
+    let mut records = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        let line_number = i + 2;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let fields = split_csv_row(&line);
+        let field = |column: usize| fields.get(column).map(|field| field.as_str()).unwrap_or("");
+        records.push(BookRecord {
+            key: parse_hash(field(key_column))
+                .ok_or_else(|| invalid_data(line_number, "invalid key"))?,
+            mv: field(move_column).parse()
+                .map_err(|_| invalid_data(line_number, "invalid move"))?,
+            weight: field(weight_column).parse()
+                .map_err(|_| invalid_data(line_number, "invalid weight"))?,
+            learn: match learn_column.map(field) {
+                Some(learn) if !learn.is_empty() => learn.parse()
+                    .map_err(|_| invalid_data(line_number, "invalid learn value"))?,
+                _ => 0
+            },
+            fen: fen_column.map(field).filter(|fen| !fen.is_empty()).map(String::from)
+        });
+    }
+    Ok(records)
+}
+
+/// Splits a CSV row into trimmed fields, handling the quoting spreadsheets add.
+fn split_csv_row(row: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = row.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            _ => field.push(c)
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Writes every entry of the book as a line of JSON, filling in FENs from `fens` where possible.
+/// Returns the number of lines written.
+#[cfg(feature = "json")]
+pub fn export_json_lines<I: Seek + Read, W: Write>(
+    reader: &mut PolyglotReader<I>,
+    mut out: W,
+    fens: &HashMap<u64, String>
+) -> Result<usize, std::io::Error> {
+    let mut count = 0;
+    for record in records(reader, fens)? {
+        serde_json::to_writer(&mut out, &record?)?;
+        writeln!(out)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Reads lines of JSON as written by `export_json_lines`. The `learn` and `fen` fields are optional.
+#[cfg(feature = "json")]
+pub fn read_json_lines<R: BufRead>(input: R) -> Result<Vec<BookRecord>, std::io::Error> {
+    let mut records = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = serde_json::from_str::<BookRecord>(&line)
+            .map_err(|error| invalid_data(i + 1, &error.to_string()))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Writes the records as a book, sorted into the order Polyglot requires.
+/// Fails if the same move appears twice for one key. Returns the number of entries written.
+pub fn write_book<W: Write>(
+    records: &[BookRecord],
+    writer: &mut PolyglotWriter<W>
+) -> Result<usize, std::io::Error> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::with_capacity(records.len());
+    for record in records {
+        if !seen.insert((record.key, record.mv)) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Duplicate move {} for key {:016x}", record.mv, record.key)
+            ));
+        }
+        entries.push((record.key, record.entry()));
+    }
+    sort_entries(&mut entries);
+    for (key, entry) in &entries {
+        writer.write(*key, entry)?;
+    }
+    Ok(entries.len())
+}
+
+fn invalid_data(line: usize, message: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Line {}: {}", line, message))
+}
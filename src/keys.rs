@@ -1,4 +1,4 @@
-/// Based on http://hgm.nubati.net/book_format.html
+//! Based on http://hgm.nubati.net/book_format.html
 
 pub const RANDOM_PIECE: [u64; 768] = [
     0x9D39247E33776D41,
@@ -787,3 +787,86 @@ pub const RANDOM_EN_PASSANT: [u64; 8] = [
     0x67A34DAC4356550B,
 ];
 pub const RANDOM_TURN: u64 = 0xF8D626AAAF278509;
+
+/// One step of SplitMix64: the next state and the random number it gives.
+fn split_mix(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (state, z ^ (z >> 31))
+}
+
+/// The random numbers keys are hashed with, in the order Polyglot's `Random64` array lists them.
+/// The default is the standard Polyglot table above; books keyed with any other table can't be read by other tools.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct KeyTable {
+    pub piece: [u64; 768],
+    pub castle: [u64; 4],
+    pub en_passant: [u64; 8],
+    pub turn: u64
+}
+
+impl KeyTable {
+    pub const STANDARD: KeyTable = KeyTable {
+        piece: RANDOM_PIECE,
+        castle: RANDOM_CASTLE,
+        en_passant: RANDOM_EN_PASSANT,
+        turn: RANDOM_TURN
+    };
+
+    /// The number of random numbers in a table, and of big-endian `u64`s in its byte form.
+    pub const LEN: usize = 781;
+
+    /// A table of random numbers generated by SplitMix64 from `seed`. The same seed always gives the same table.
+    pub fn from_seed(seed: u64) -> KeyTable {
+        let mut state = seed;
+        let mut next = || {
+            let (next_state, random) = split_mix(state);
+            state = next_state;
+            random
+        };
+        let mut table = KeyTable::STANDARD;
+        table.piece.iter_mut()
+            .chain(&mut table.castle)
+            .chain(&mut table.en_passant)
+            .chain(std::iter::once(&mut table.turn))
+            .for_each(|random| *random = next());
+        table
+    }
+
+    /// Reads a table from `LEN` big-endian `u64`s. Returns `None` if `bytes` is the wrong length.
+    pub fn from_bytes(bytes: &[u8]) -> Option<KeyTable> {
+        if bytes.len() != KeyTable::LEN * 8 {
+            return None;
+        }
+        let mut randoms = bytes.chunks(8).map(|chunk| {
+            let mut random = [0; 8];
+            random.copy_from_slice(chunk);
+            u64::from_be_bytes(random)
+        });
+        let mut table = KeyTable::STANDARD;
+        table.piece.iter_mut()
+            .chain(&mut table.castle)
+            .chain(&mut table.en_passant)
+            .chain(std::iter::once(&mut table.turn))
+            .zip(&mut randoms)
+            .for_each(|(random, read)| *random = read);
+        Some(table)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.piece.iter()
+            .chain(&self.castle)
+            .chain(&self.en_passant)
+            .chain(std::iter::once(&self.turn))
+            .flat_map(|random| random.to_be_bytes())
+            .collect()
+    }
+}
+
+impl Default for KeyTable {
+    fn default() -> Self {
+        KeyTable::STANDARD
+    }
+}
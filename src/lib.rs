@@ -1,8 +1,42 @@
-use std::io::{Read,Seek,SeekFrom};
+use std::io::{Read,Seek,SeekFrom,Write};
+
+use keys::KeyTable;
 
 pub mod keys;
+pub mod board;
+pub mod build;
+pub mod cache;
+pub mod chain;
+pub mod collisions;
+pub mod diff;
+pub mod eco;
+pub mod fen;
+pub mod filter;
+pub mod graph;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod interchange;
+pub mod pgn;
+pub mod prune;
+#[cfg(feature = "python")]
+mod python;
+pub mod repertoire;
+pub mod replay;
+pub mod san;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "serde")]
+pub mod serde_hex;
+pub mod sort;
+pub mod stats;
+pub mod transform;
+pub mod variant;
+pub mod walk;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Side {
     White,
     Black
@@ -30,7 +64,17 @@ impl From<Side> for chess::Color {
     }
 }
 
+impl Side {
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::White => Side::Black,
+            Side::Black => Side::White
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PieceType {
     Pawn,
     Knight,
@@ -79,9 +123,33 @@ impl PieceType {
             PieceType::King => 5
         }
     }
+    /// The piece type for a FEN/SAN letter, ignoring case.
+    pub fn from_char(c: char) -> Option<PieceType> {
+        match c.to_ascii_lowercase() {
+            'p' => Some(PieceType::Pawn),
+            'n' => Some(PieceType::Knight),
+            'b' => Some(PieceType::Bishop),
+            'r' => Some(PieceType::Rook),
+            'q' => Some(PieceType::Queen),
+            'k' => Some(PieceType::King),
+            _ => None
+        }
+    }
+    /// The lowercase FEN letter of the piece type.
+    pub fn to_char(self) -> char {
+        match self {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k'
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Piece {
     pub piece_type: PieceType,
     pub side: Side,
@@ -90,12 +158,24 @@ pub struct Piece {
 
 impl Piece {
     pub fn polyglot_hash(&self) -> u64 {
+        self.polyglot_hash_with(&KeyTable::STANDARD)
+    }
+    pub fn polyglot_hash_with(&self, table: &KeyTable) -> u64 {
         let kind = self.piece_type.index() * 2 + (self.side == Side::White) as usize;
-        keys::RANDOM_PIECE[64 * kind + 8 * self.square.rank + self.square.file]
+        table.piece[64 * kind + self.square.index()]
+    }
+    /// The same piece with its colour swapped, on the square mirrored across the board.
+    pub fn mirrored(&self) -> Piece {
+        Piece {
+            piece_type: self.piece_type,
+            side: self.side.opponent(),
+            square: self.square.mirrored()
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CastleRights {
     pub queen_side: bool,
     pub king_side: bool
@@ -125,6 +205,9 @@ impl From<CastleRights> for chess::CastleRights {
 
 impl CastleRights {
     pub fn polyglot_hash(&self, side: Side) -> u64 {
+        self.polyglot_hash_with(side, &KeyTable::STANDARD)
+    }
+    pub fn polyglot_hash_with(&self, side: Side, table: &KeyTable) -> u64 {
         let mut hash = 0;
         let base = if side == Side::White {
             0
@@ -132,40 +215,63 @@ impl CastleRights {
             2
         };
         if self.king_side {
-            hash ^= keys::RANDOM_CASTLE[base];
+            hash ^= table.castle[base];
         }
         if self.queen_side {
-            hash ^= keys::RANDOM_CASTLE[base + 1];
+            hash ^= table.castle[base + 1];
         }
         hash
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct PolyglotKey {
     pub pieces: Vec<Piece>,
     pub white_castle: CastleRights,
     pub black_castle: CastleRights,
     pub en_passant_file: Option<usize>,
-    pub turn: Side
+    pub turn: Side,
+    /// State for variants whose positions standard keys can't tell apart. `None` for standard chess.
+    pub variant: Option<variant::VariantState>
 }
 
 impl PolyglotKey {
     pub fn polyglot_hash(&self) -> u64 {
+        self.polyglot_hash_with(&KeyTable::STANDARD)
+    }
+    /// Hashes the key with a custom table. Variant state is hashed the same way whatever the table.
+    pub fn polyglot_hash_with(&self, table: &KeyTable) -> u64 {
         let mut hash = 0;
         for piece in &self.pieces {
-            hash ^= piece.polyglot_hash();
+            hash ^= piece.polyglot_hash_with(table);
         }
-        hash ^= self.white_castle.polyglot_hash(Side::White);
-        hash ^= self.black_castle.polyglot_hash(Side::Black);
+        hash ^= self.white_castle.polyglot_hash_with(Side::White, table);
+        hash ^= self.black_castle.polyglot_hash_with(Side::Black, table);
         if let Some(file) = self.en_passant_file {
-            hash ^= keys::RANDOM_EN_PASSANT[file];
+            hash ^= table.en_passant[file];
         }
         if self.turn == Side::White {
-            hash ^= keys::RANDOM_TURN;
+            hash ^= table.turn;
+        }
+        if let Some(variant) = &self.variant {
+            hash ^= variant.polyglot_hash();
         }
         hash
     }
+    pub fn piece_on(&self, square: Square) -> Option<Piece> {
+        self.pieces.iter().find(|piece| piece.square == square).copied()
+    }
+    /// The colour-mirrored position: ranks flipped, colours, castling rights and turn swapped.
+    pub fn mirrored(&self) -> PolyglotKey {
+        PolyglotKey {
+            pieces: self.pieces.iter().map(Piece::mirrored).collect(),
+            white_castle: self.black_castle,
+            black_castle: self.white_castle,
+            en_passant_file: self.en_passant_file,
+            turn: self.turn.opponent(),
+            variant: self.variant.map(|variant| variant.mirrored())
+        }
+    }
     #[cfg(feature = "chess_lib_helpers")]
     pub fn from_board(board: &chess::Board) -> Self {
         let pieces: Vec<_> = board.combined().into_iter().map(|sq| Piece {
@@ -191,39 +297,93 @@ impl PolyglotKey {
                         }
                     })
             }),
-            turn: board.side_to_move().into()
+            turn: board.side_to_move().into(),
+            variant: None
         }
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct Square {
-    pub rank: usize,
-    pub file: usize
+/// A square on the board, stored as its index from 0 (a1) to 63 (h8).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Square(u8);
+
+impl Square {
+    pub fn new(rank: usize, file: usize) -> Option<Square> {
+        if rank < 8 && file < 8 {
+            Some(Square((rank * 8 + file) as u8))
+        } else {
+            None
+        }
+    }
+    pub fn from_index(index: usize) -> Option<Square> {
+        if index < 64 {
+            Some(Square(index as u8))
+        } else {
+            None
+        }
+    }
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+    pub fn rank(self) -> usize {
+        self.index() / 8
+    }
+    pub fn file(self) -> usize {
+        self.index() % 8
+    }
+    pub fn mirrored(self) -> Square {
+        Square(self.0 ^ 0b111000)
+    }
+}
+
+impl std::fmt::Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file() as u8) as char, (b'1' + self.rank() as u8) as char)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseSquareError;
+
+impl std::fmt::Display for ParseSquareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid square")
+    }
+}
+
+impl std::error::Error for ParseSquareError {}
+
+impl std::str::FromStr for Square {
+    type Err = ParseSquareError;
+
+    fn from_str(s: &str) -> Result<Square, ParseSquareError> {
+        match s.as_bytes() {
+            &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Ok(Square((rank - b'1') * 8 + file - b'a')),
+            _ => Err(ParseSquareError)
+        }
+    }
 }
 
 #[cfg(feature = "chess_lib_helpers")]
 impl From<chess::Square> for Square {
     fn from(sq: chess::Square) -> Square {
-        Square {
-            rank: sq.get_rank().to_index(),
-            file: sq.get_file().to_index()
-        }
+        Square(sq.to_int())
     }
 }
 
 #[cfg(feature = "chess_lib_helpers")]
 impl From<Square> for chess::Square {
     fn from(sq: Square) -> chess::Square {
-        chess::Square::make_square(chess::Rank::from_index(sq.rank), chess::File::from_index(sq.file))
+        chess::Square::make_square(chess::Rank::from_index(sq.rank()), chess::File::from_index(sq.file()))
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// A move as stored in a Polyglot book. Castling is encoded as the king capturing its own rook.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Move {
-    pub source: Square,
-    pub dest: Square,
-    pub promotion: Option<PieceType>
+    source: Square,
+    dest: Square,
+    promotion: Option<PieceType>
 }
 
 #[cfg(feature = "chess_lib_helpers")]
@@ -245,76 +405,277 @@ impl From<Move> for chess::ChessMove {
 }
 
 impl Move {
+    /// Returns `None` if the promotion isn't to a knight, bishop, rook or queen.
+    pub fn new(source: Square, dest: Square, promotion: Option<PieceType>) -> Option<Move> {
+        match promotion {
+            Some(PieceType::Pawn) | Some(PieceType::King) => None,
+            _ => Some(Move {
+                source,
+                dest,
+                promotion
+            })
+        }
+    }
+    pub fn source(self) -> Square {
+        self.source
+    }
+    pub fn dest(self) -> Square {
+        self.dest
+    }
+    pub fn promotion(self) -> Option<PieceType> {
+        self.promotion
+    }
+    /// Decodes a Polyglot move, ignoring the unused top bit. Panics on an invalid promotion; see `try_from_u16`.
     pub fn from_u16(mv: u16) -> Self {
-        fn index(mv: u16, i: usize) -> usize {
-            ((mv >> (i * 3)) & 0b111) as usize
+        match Self::try_from_u16(mv & 0x7FFF) {
+            Some(decoded) => decoded,
+            None => panic!("Invalid promotion {}", (mv >> 12) & 0b111)
         }
-        Self {
-            dest: Square {
-                file: index(mv, 0),
-                rank: index(mv, 1)
-            },
-            source: Square {
-                file: index(mv, 2),
-                rank: index(mv, 3)
-            },
-            promotion: match index(mv, 4) {
-                0 => None,
-                1 => Some(PieceType::Knight),
-                2 => Some(PieceType::Bishop),
-                3 => Some(PieceType::Rook),
-                4 => Some(PieceType::Queen),
-                p => unreachable!("Invalid promotion {}", p)
-            }
+    }
+    /// Decodes a Polyglot move, returning `None` if it has an invalid promotion or the unused top bit set.
+    pub fn try_from_u16(mv: u16) -> Option<Self> {
+        fn square(mv: u16, i: usize) -> Square {
+            Square(((mv >> (i * 6)) & 0b111111) as u8)
+        }
+        let promotion = match mv >> 12 {
+            0 => None,
+            1 => Some(PieceType::Knight),
+            2 => Some(PieceType::Bishop),
+            3 => Some(PieceType::Rook),
+            4 => Some(PieceType::Queen),
+            _ => return None
+        };
+        Some(Self {
+            dest: square(mv, 0),
+            source: square(mv, 1),
+            promotion
+        })
+    }
+    /// The move with both squares mirrored across the board. Castling keeps its king-takes-rook encoding.
+    pub fn mirrored(self) -> Move {
+        Move {
+            source: self.source.mirrored(),
+            dest: self.dest.mirrored(),
+            promotion: self.promotion
+        }
+    }
+    /// Converts Polyglot castling (king takes own rook) into standard castling (king moves two squares).
+    /// Other moves are returned unchanged.
+    pub fn to_standard_castling(self, position: &PolyglotKey) -> Move {
+        let king = match self.castling_king(position) {
+            Some(king) => king,
+            None => return self
+        };
+        let takes_own_rook = position.piece_on(self.dest).is_some_and(|rook| {
+            rook.piece_type == PieceType::Rook && rook.side == king.side
+        });
+        match self.dest.file() {
+            0 if takes_own_rook => Move::new(self.source, Square::new(self.dest.rank(), 2).unwrap(), None).unwrap(),
+            7 if takes_own_rook => Move::new(self.source, Square::new(self.dest.rank(), 6).unwrap(), None).unwrap(),
+            _ => self
         }
     }
+    /// Converts standard castling (king moves two squares) into Polyglot castling (king takes own rook).
+    /// Other moves are returned unchanged.
+    pub fn from_standard_castling(self, position: &PolyglotKey) -> Move {
+        if self.castling_king(position).is_none() {
+            return self;
+        }
+        match self.dest.file() {
+            2 => Move::new(self.source, Square::new(self.dest.rank(), 0).unwrap(), None).unwrap(),
+            6 => Move::new(self.source, Square::new(self.dest.rank(), 7).unwrap(), None).unwrap(),
+            _ => self
+        }
+    }
+    /// The king making this move, if it starts on its initial square and stays on its home rank.
+    fn castling_king(self, position: &PolyglotKey) -> Option<Piece> {
+        let king = position.piece_on(self.source)?;
+        let home_rank = if king.side == Side::White { 0 } else { 7 };
+        if king.piece_type == PieceType::King &&
+            self.source.rank() == home_rank &&
+            self.source.file() == 4 &&
+            self.dest.rank() == home_rank {
+            Some(king)
+        } else {
+            None
+        }
+    }
+    pub fn to_u16(&self) -> u16 {
+        let promotion = match self.promotion {
+            None => 0,
+            Some(PieceType::Knight) => 1,
+            Some(PieceType::Bishop) => 2,
+            Some(PieceType::Rook) => 3,
+            Some(PieceType::Queen) => 4,
+            Some(p) => unreachable!("Invalid promotion {:?}", p)
+        };
+        (promotion << 12) | (self.source.0 as u16) << 6 | self.dest.0 as u16
+    }
+}
+
+/// Formats the move in UCI notation, as stored. Use `to_standard_castling` first for standard castling.
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.source, self.dest)?;
+        if let Some(promotion) = self.promotion {
+            write!(f, "{}", promotion.to_char())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ParseMoveError;
+
+impl std::fmt::Display for ParseMoveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid UCI move")
+    }
+}
+
+impl std::error::Error for ParseMoveError {}
+
+/// Parses a move in UCI notation. Use `from_standard_castling` afterwards to accept standard castling.
+impl std::str::FromStr for Move {
+    type Err = ParseMoveError;
+
+    fn from_str(s: &str) -> Result<Move, ParseMoveError> {
+        if !s.is_ascii() || !(s.len() == 4 || s.len() == 5) {
+            return Err(ParseMoveError);
+        }
+        let source = s[0..2].parse().map_err(|_| ParseMoveError)?;
+        let dest = s[2..4].parse().map_err(|_| ParseMoveError)?;
+        let promotion = match s[4..].chars().next() {
+            Some(c) if c.is_ascii_lowercase() => Some(PieceType::from_char(c).ok_or(ParseMoveError)?),
+            Some(_) => return Err(ParseMoveError),
+            None => None
+        };
+        Move::new(source, dest, promotion).ok_or(ParseMoveError)
+    }
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PolyglotEntry {
     pub mv: Move,
-    pub weight: u16
+    pub weight: u16,
+    pub learn: u32
 }
 
 impl PolyglotEntry {
     pub const SIZE: usize = 16;
-    pub fn from_bytes(bytes: &[u8]) -> Self {
+    /// Decodes the 8 bytes following an entry's key. Moves `Move::try_from_u16` rejects are `InvalidData` errors.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, std::io::Error> {
         let mut mv = [0; 2];
         mv.copy_from_slice(&bytes[0..2]);
 
         let mut weight = [0; 2];
         weight.copy_from_slice(&bytes[2..4]);
 
-        // The rest is the learn value, but it's not implemented.
+        let mut learn = [0; 4];
+        learn.copy_from_slice(&bytes[4..8]);
+
+        let mv = u16::from_be_bytes(mv);
+        Ok(Self {
+            mv: Move::try_from_u16(mv).ok_or_else(|| std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid move {:#06x}", mv)
+            ))?,
+            weight: u16::from_be_bytes(weight),
+            learn: u32::from_be_bytes(learn)
+        })
+    }
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let mut bytes = [0; 8];
+        bytes[0..2].copy_from_slice(&self.mv.to_u16().to_be_bytes());
+        bytes[2..4].copy_from_slice(&self.weight.to_be_bytes());
+        bytes[4..8].copy_from_slice(&self.learn.to_be_bytes());
+        bytes
+    }
+}
+
+/// Sorts entries into book order: by key, then by descending weight.
+pub fn sort_entries(entries: &mut [(u64, PolyglotEntry)]) {
+    entries.sort_by(|(key_a, entry_a), (key_b, entry_b)| {
+        key_a.cmp(key_b).then(entry_b.weight.cmp(&entry_a.weight))
+    });
+}
+
+#[derive(Debug)]
+pub struct PolyglotWriter<W> {
+    inner: W,
+    last_key: Option<u64>,
+    len: u64
+}
 
+impl<W: Write> PolyglotWriter<W> {
+    pub fn new(inner: W) -> Self {
         Self {
-            mv: Move::from_u16(u16::from_be_bytes(mv)),
-            weight: u16::from_be_bytes(weight)
+            inner,
+            last_key: None,
+            len: 0
         }
     }
+    /// Appends an entry to the book. Entries must be written in ascending key order;
+    /// `sort::ExternalSorter` can put them in order first.
+    pub fn write(&mut self, key: u64, entry: &PolyglotEntry) -> Result<(), std::io::Error> {
+        if self.last_key.is_some_and(|last_key| key < last_key) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Key {:016x} written out of order", key)
+            ));
+        }
+        self.inner.write_all(&key.to_be_bytes())?;
+        self.inner.write_all(&entry.to_bytes())?;
+        self.last_key = Some(key);
+        self.len += 1;
+        Ok(())
+    }
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.inner.flush()
+    }
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
 }
 
 #[derive(Debug)]
 pub struct PolyglotReader<I> {
     inner: I,
-    len: u64
+    len: u64,
+    table: Box<KeyTable>
 }
 
 impl <I: Seek + Read> PolyglotReader<I> {
     pub fn new(inner: I) -> Result<Self, std::io::Error> {
+        Self::with_table(inner, KeyTable::STANDARD)
+    }
+    /// Opens a book keyed with a custom table. `get` and everything that hashes positions for this reader use it.
+    pub fn with_table(inner: I, table: KeyTable) -> Result<Self, std::io::Error> {
         let mut inner = inner;
         Ok(Self {
             len: inner.seek(SeekFrom::End(0))? / PolyglotEntry::SIZE as u64,
-            inner
+            inner,
+            table: Box::new(table)
         })
     }
+    pub fn table(&self) -> &KeyTable {
+        &self.table
+    }
     pub fn get(&mut self, key: &PolyglotKey) -> Result<Vec<PolyglotEntry>, std::io::Error> {
-        let hash = key.polyglot_hash();
-        
+        self.get_hash(key.polyglot_hash_with(&self.table))
+    }
+    pub fn get_hash(&mut self, hash: u64) -> Result<Vec<PolyglotEntry>, std::io::Error> {
         let mut entry_exists = false;
 
         let mut left = 0;
-        let mut right = self.len - 1;
+        let mut right = self.len;
         while left < right {
             let middle = (left + right) / 2;
             self.inner.seek(SeekFrom::Start(middle * PolyglotEntry::SIZE as u64))?;
@@ -341,7 +702,7 @@ impl <I: Seek + Read> PolyglotReader<I> {
         left = 0;
         right = self.len - 1;
         while left < right {
-            let middle = (left + right + 1) / 2;
+            let middle = (left + right).div_ceil(2);
             self.inner.seek(SeekFrom::Start(middle * PolyglotEntry::SIZE as u64))?;
             
             let mut entry_key = [0; 8];
@@ -361,13 +722,87 @@ impl <I: Seek + Read> PolyglotReader<I> {
         self.inner.seek(SeekFrom::Start(lower_bound * PolyglotEntry::SIZE as u64))?;
         self.inner.read_exact(&mut entries)?;
         
-        let entries = entries.chunks(PolyglotEntry::SIZE)
+        entries.chunks(PolyglotEntry::SIZE)
             .map(|entry| PolyglotEntry::from_bytes(&entry[8..]))
-            .collect();
-
-        Ok(entries)
+            .collect()
+    }
+    /// Iterates over every entry in the book in file order, alongside its key.
+    pub fn entries(&mut self) -> Result<Entries<'_, I>, std::io::Error> {
+        self.inner.seek(SeekFrom::Start(0))?;
+        Ok(Entries {
+            reader: self,
+            index: 0
+        })
+    }
+    /// Iterates over every position in the book in file order, grouping the entries of each key.
+    pub fn positions(&mut self) -> Result<Positions<'_, I>, std::io::Error> {
+        Ok(Positions {
+            entries: self.entries()?,
+            pending: None
+        })
     }
     pub fn len(&self) -> usize {
         self.len as usize
     }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+#[derive(Debug)]
+pub struct Entries<'a, I> {
+    reader: &'a mut PolyglotReader<I>,
+    index: u64
+}
+
+impl<I: Read> Iterator for Entries<'_, I> {
+    type Item = Result<(u64, PolyglotEntry), std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.reader.len {
+            return None;
+        }
+        self.index += 1;
+
+        let mut entry = [0; PolyglotEntry::SIZE];
+        if let Err(error) = self.reader.inner.read_exact(&mut entry) {
+            self.index = self.reader.len;
+            return Some(Err(error));
+        }
+        let mut key = [0; 8];
+        key.copy_from_slice(&entry[0..8]);
+        Some(PolyglotEntry::from_bytes(&entry[8..]).map(|entry| (u64::from_be_bytes(key), entry)))
+    }
+}
+
+#[derive(Debug)]
+pub struct Positions<'a, I> {
+    entries: Entries<'a, I>,
+    pending: Option<(u64, PolyglotEntry)>
+}
+
+impl<I: Read> Iterator for Positions<'_, I> {
+    type Item = Result<(u64, Vec<PolyglotEntry>), std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, first) = match self.pending.take() {
+            Some(pending) => pending,
+            None => match self.entries.next()? {
+                Ok(entry) => entry,
+                Err(error) => return Some(Err(error))
+            }
+        };
+        let mut entries = vec![first];
+        for entry in &mut self.entries {
+            match entry {
+                Ok((entry_key, entry)) if entry_key == key => entries.push(entry),
+                Ok(entry) => {
+                    self.pending = Some(entry);
+                    break;
+                }
+                Err(error) => return Some(Err(error))
+            }
+        }
+        Some(Ok((key, entries)))
+    }
 }
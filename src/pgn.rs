@@ -0,0 +1,216 @@
+//! Reading games from PGN files.
+
+use std::io::BufRead;
+
+use crate::Side;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw
+}
+
+impl GameResult {
+    /// Parses a result as written in PGN. Unfinished games (`*`) have no result.
+    pub fn from_pgn(result: &str) -> Option<GameResult> {
+        match result {
+            "1-0" => Some(GameResult::WhiteWins),
+            "0-1" => Some(GameResult::BlackWins),
+            "1/2-1/2" => Some(GameResult::Draw),
+            _ => None
+        }
+    }
+
+    /// The result of a win for `side`.
+    pub fn winner(side: Side) -> GameResult {
+        match side {
+            Side::White => GameResult::WhiteWins,
+            Side::Black => GameResult::BlackWins
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PgnGame {
+    /// The tag pairs in the order they appear.
+    pub tags: Vec<(String, String)>,
+    /// The moves of the main line in SAN, without move numbers, comments, annotations or variations.
+    pub moves: Vec<String>,
+    /// The result from the end of the movetext, or else from the `Result` tag.
+    pub result: Option<GameResult>
+}
+
+impl PgnGame {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// Reads games one at a time from PGN text. Games are separated by their tag sections
+/// or the result that ends their movetext.
+#[derive(Debug)]
+pub struct PgnReader<R> {
+    input: R,
+    line: String,
+    /// A tag line read while finishing the previous game.
+    pending: Option<String>
+}
+
+impl<R: BufRead> PgnReader<R> {
+    pub fn new(input: R) -> Self {
+        Self {
+            input,
+            line: String::new(),
+            pending: None
+        }
+    }
+
+    fn next_line(&mut self) -> Result<Option<String>, std::io::Error> {
+        if let Some(line) = self.pending.take() {
+            return Ok(Some(line));
+        }
+        self.line.clear();
+        if self.input.read_line(&mut self.line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(self.line.trim().to_string()))
+    }
+
+    pub fn read_game(&mut self) -> Result<Option<PgnGame>, std::io::Error> {
+        let mut game = PgnGame::default();
+        let mut movetext = Movetext::default();
+        let mut started = false;
+        while let Some(line) = self.next_line()? {
+            if line.starts_with('%') {
+                continue;
+            }
+            if line.starts_with('[') && movetext.depth() == 0 {
+                if !movetext.is_empty() {
+                    self.pending = Some(line);
+                    break;
+                }
+                if let Some(tag) = parse_tag(&line) {
+                    game.tags.push(tag);
+                }
+                started = true;
+                continue;
+            }
+            if line.is_empty() && !started {
+                continue;
+            }
+            started = true;
+            if movetext.feed(&line, &mut game) {
+                break;
+            }
+        }
+        if !started {
+            return Ok(None);
+        }
+        if movetext.result.is_none() {
+            movetext.result = game.tag("Result").and_then(GameResult::from_pgn);
+        }
+        game.result = movetext.result;
+        Ok(Some(game))
+    }
+}
+
+impl<R: BufRead> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, std::io::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_game().transpose()
+    }
+}
+
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.trim_end().strip_suffix(']')?;
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            _ => unescaped.push(c)
+        }
+    }
+    Some((name.to_string(), unescaped))
+}
+
+#[derive(Debug, Default)]
+struct Movetext {
+    in_comment: bool,
+    variation_depth: usize,
+    tokens: usize,
+    result: Option<GameResult>
+}
+
+impl Movetext {
+    fn depth(&self) -> usize {
+        self.variation_depth + self.in_comment as usize
+    }
+
+    fn is_empty(&self) -> bool {
+        self.tokens == 0
+    }
+
+    /// Adds a line of movetext to the game. Returns whether it contained the game's termination marker.
+    fn feed(&mut self, line: &str, game: &mut PgnGame) -> bool {
+        let mut token = String::new();
+        let mut chars = line.chars();
+        loop {
+            let c = chars.next();
+            if self.in_comment {
+                if c == Some('}') {
+                    self.in_comment = false;
+                }
+                if c.is_none() {
+                    return false;
+                }
+                continue;
+            }
+            let ends_token = match c {
+                None | Some(' ' | '\t' | '{' | ';' | '(' | ')') => true,
+                Some(c) => {
+                    token.push(c);
+                    false
+                }
+            };
+            if ends_token && !token.is_empty() && self.token(&std::mem::take(&mut token), game) {
+                return true;
+            }
+            match c {
+                None | Some(';') => return false,
+                Some('{') => self.in_comment = true,
+                Some('(') => self.variation_depth += 1,
+                Some(')') => self.variation_depth = self.variation_depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+    }
+
+    fn token(&mut self, token: &str, game: &mut PgnGame) -> bool {
+        self.tokens += 1;
+        if self.variation_depth > 0 {
+            return false;
+        }
+        if matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+            self.result = GameResult::from_pgn(token);
+            return true;
+        }
+        if token.starts_with('$') {
+            return false;
+        }
+        // Move numbers may be attached to the move, as in `1.e4` or `12...Nf6`.
+        let san = match token.find(|c: char| !c.is_ascii_digit()) {
+            Some(i) if token[i..].starts_with('.') => token[i..].trim_start_matches('.'),
+            Some(_) => token,
+            None => ""
+        };
+        if !san.is_empty() {
+            game.moves.push(san.to_string());
+        }
+        false
+    }
+}
@@ -0,0 +1,106 @@
+use std::io::{Read, Seek, Write};
+
+use crate::board::Board;
+use crate::{PolyglotEntry, PolyglotReader, PolyglotWriter};
+
+#[derive(Debug, Clone, Default)]
+pub struct PruneOptions {
+    /// Entries with a weight below this are removed.
+    pub min_weight: u16,
+    /// Entries whose share of their position's total weight is below this are removed.
+    pub min_weight_share: f64,
+    /// If set, positions that can't be reached through the remaining book moves are removed.
+    pub reachability: Option<Reachability>
+}
+
+#[derive(Debug, Clone)]
+pub struct Reachability {
+    pub roots: Vec<Board>,
+    /// Positions further than this many plies from every root are removed.
+    pub max_plies: Option<usize>
+}
+
+impl Default for Reachability {
+    fn default() -> Self {
+        Self {
+            roots: vec![Board::default()],
+            max_plies: None
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct PruneSummary {
+    pub kept_entries: usize,
+    pub kept_positions: usize,
+    pub removed_positions: usize,
+    pub removed_by_weight: usize,
+    pub removed_by_weight_share: usize,
+    pub removed_unreachable: usize
+}
+
+impl PruneSummary {
+    pub fn removed_entries(&self) -> usize {
+        self.removed_by_weight + self.removed_by_weight_share + self.removed_unreachable
+    }
+}
+
+impl PruneOptions {
+    fn retain(&self, entries: &mut Vec<PolyglotEntry>, summary: &mut PruneSummary) {
+        let total: u64 = entries.iter().map(|entry| entry.weight as u64).sum();
+        entries.retain(|entry| {
+            if entry.weight < self.min_weight {
+                summary.removed_by_weight += 1;
+                return false;
+            }
+            let share = if total == 0 {
+                0.0
+            } else {
+                entry.weight as f64 / total as f64
+            };
+            if share < self.min_weight_share {
+                summary.removed_by_weight_share += 1;
+                return false;
+            }
+            true
+        });
+    }
+}
+
+/// Copies the entries of `reader` that survive pruning into `writer`.
+pub fn prune<I: Seek + Read, W: Write>(
+    reader: &mut PolyglotReader<I>,
+    writer: &mut PolyglotWriter<W>,
+    options: &PruneOptions
+) -> Result<PruneSummary, std::io::Error> {
+    let reachable = match &options.reachability {
+        Some(reachability) => Some(crate::walk::reachable_lines_filtered(
+            reader,
+            &reachability.roots,
+            reachability.max_plies,
+            |_, entries| options.retain(entries, &mut PruneSummary::default())
+        )?),
+        None => None
+    };
+
+    let mut summary = PruneSummary::default();
+    for position in reader.positions()? {
+        let (key, mut entries) = position?;
+        options.retain(&mut entries, &mut summary);
+        if reachable.as_ref().is_some_and(|reachable| !reachable.contains_key(&key)) {
+            summary.removed_unreachable += entries.len();
+            entries.clear();
+        }
+
+        if entries.is_empty() {
+            summary.removed_positions += 1;
+            continue;
+        }
+        for entry in &entries {
+            writer.write(key, entry)?;
+        }
+        summary.kept_entries += entries.len();
+        summary.kept_positions += 1;
+    }
+    Ok(summary)
+}
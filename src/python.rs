@@ -0,0 +1,64 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::{PolyglotKey, PolyglotReader};
+
+type Probe = Vec<(String, u16, u32)>;
+
+fn parse_fen(fen: &str) -> PyResult<PolyglotKey> {
+    fen.parse().map_err(|_| PyValueError::new_err(format!("invalid FEN '{}'", fen)))
+}
+
+/// A Polyglot opening book.
+#[pyclass]
+struct Book {
+    reader: PolyglotReader<BufReader<File>>
+}
+
+#[pymethods]
+impl Book {
+    #[new]
+    fn new(path: PathBuf) -> PyResult<Self> {
+        Ok(Self {
+            reader: PolyglotReader::new(BufReader::new(File::open(path)?))?
+        })
+    }
+
+    /// Returns a `(move, weight, learn)` tuple for each book move in the position.
+    /// Moves are in UCI notation, with castling as the king moving two squares.
+    fn probe(&mut self, fen: &str) -> PyResult<Probe> {
+        let key = parse_fen(fen)?;
+        let entries = self.reader.get(&key)?;
+        Ok(entries.iter().map(|entry| {
+            (entry.mv.to_standard_castling(&key).to_string(), entry.weight, entry.learn)
+        }).collect())
+    }
+
+    /// Like `probe`, but looks the position up by its Polyglot hash.
+    /// Castling moves are left as the king taking its own rook, since the position isn't known.
+    fn probe_hash(&mut self, hash: u64) -> PyResult<Probe> {
+        let entries = self.reader.get_hash(hash)?;
+        Ok(entries.iter().map(|entry| (entry.mv.to_string(), entry.weight, entry.learn)).collect())
+    }
+
+    fn __len__(&self) -> usize {
+        self.reader.len()
+    }
+}
+
+/// Returns the Polyglot hash of the position.
+#[pyfunction]
+fn hash(fen: &str) -> PyResult<u64> {
+    Ok(parse_fen(fen)?.polyglot_hash())
+}
+
+#[pymodule]
+fn chess_polyglot_reader(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Book>()?;
+    module.add_function(wrap_pyfunction!(hash, module)?)?;
+    Ok(())
+}
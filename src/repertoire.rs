@@ -0,0 +1,198 @@
+//! Narrowing a book to a repertoire for one side, keeping every reply for the other.
+
+use std::collections::HashMap;
+use std::io::{BufRead, Read, Seek, Write};
+
+use crate::board::Board;
+use crate::pgn::PgnReader;
+use crate::prune::Reachability;
+use crate::{sort_entries, Move, PolyglotEntry, PolyglotKey, PolyglotReader, PolyglotWriter, Side};
+
+/// The moves chosen for one side, along with the position they are played from.
+/// Moves are stored with Polyglot's castling notation, as books store them.
+#[derive(Debug, Clone)]
+pub struct Repertoire {
+    side: Side,
+    /// Keyed by the standard Polyglot hash. `narrow` rehashes the positions with the book's table.
+    moves: HashMap<u64, (PolyglotKey, Vec<Move>)>
+}
+
+impl Repertoire {
+    pub fn new(side: Side) -> Self {
+        Self {
+            side,
+            moves: HashMap::new()
+        }
+    }
+
+    /// Reads a repertoire from the main line of every game in `input`. Games start from their `FEN` tag if they have one.
+    /// Variations aren't followed, so each line of the repertoire needs its own game.
+    pub fn from_pgn<R: BufRead>(side: Side, input: R) -> Result<Self, std::io::Error> {
+        let mut repertoire = Self::new(side);
+        for (i, game) in PgnReader::new(input).enumerate() {
+            let game = game?;
+            let invalid = |message: String| std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Game {}: {}", i + 1, message)
+            );
+            let mut board = match game.tag("FEN") {
+                Some(fen) => fen.parse().map_err(|_| invalid(format!("invalid FEN '{}'", fen)))?,
+                None => Board::default()
+            };
+            for san in &game.moves {
+                let mv = board.parse_san(san).ok_or_else(|| invalid(format!("illegal move '{}'", san)))?;
+                repertoire.add(&board, mv);
+                board = board.play(mv).unwrap();
+            }
+        }
+        Ok(repertoire)
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// Adds `mv` to the moves chosen in `board`. Returns false, adding nothing,
+    /// if it isn't the repertoire side's turn or the move is illegal.
+    pub fn add(&mut self, board: &Board, mv: Move) -> bool {
+        if board.turn() != self.side || !board.is_legal(mv) {
+            return false;
+        }
+        let key = board.key();
+        let mv = mv.to_standard_castling(&key).from_standard_castling(&key);
+        let (_, moves) = self.moves.entry(key.polyglot_hash()).or_insert_with(|| (key, Vec::new()));
+        if !moves.contains(&mv) {
+            moves.push(mv);
+        }
+        true
+    }
+
+    /// The moves chosen in the position with the given standard Polyglot hash, or nothing if the repertoire doesn't cover it.
+    pub fn moves(&self, hash: u64) -> &[Move] {
+        self.moves.get(&hash).map_or(&[], |(_, moves)| moves.as_slice())
+    }
+
+    /// The number of positions the repertoire covers.
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NarrowOptions {
+    /// The weight given to repertoire moves the book doesn't have. If unset, they are left out.
+    pub missing_weight: Option<u16>,
+    /// If set, positions that can't be reached through the narrowed book, such as replies to dropped moves, are removed.
+    pub reachability: Option<Reachability>
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct NarrowSummary {
+    pub kept_entries: usize,
+    /// Positions covered by the repertoire.
+    pub narrowed_positions: usize,
+    /// Entries for the repertoire side that aren't repertoire moves.
+    pub removed_entries: usize,
+    /// Repertoire moves added with `missing_weight`.
+    pub added_entries: usize,
+    pub removed_unreachable: usize
+}
+
+fn narrow_position(
+    repertoire: &HashMap<u64, &[Move]>,
+    hash: u64,
+    entries: &mut Vec<PolyglotEntry>,
+    missing_weight: Option<u16>,
+    summary: &mut NarrowSummary
+) {
+    let moves = match repertoire.get(&hash) {
+        Some(&moves) => moves,
+        None => return
+    };
+    summary.narrowed_positions += 1;
+    let len = entries.len();
+    entries.retain(|entry| moves.contains(&entry.mv));
+    summary.removed_entries += len - entries.len();
+    if let Some(weight) = missing_weight {
+        for &mv in moves {
+            if entries.iter().all(|entry| entry.mv != mv) {
+                entries.push(PolyglotEntry {
+                    mv,
+                    weight,
+                    learn: 0
+                });
+                summary.added_entries += 1;
+            }
+        }
+    }
+}
+
+/// Copies `reader` into `writer`, leaving the repertoire side only its repertoire moves in every position the repertoire covers.
+/// Positions the repertoire doesn't cover, including all of the other side's, are copied unchanged.
+/// Positions are looked up with the reader's key table.
+pub fn narrow<I: Seek + Read, W: Write>(
+    reader: &mut PolyglotReader<I>,
+    writer: &mut PolyglotWriter<W>,
+    repertoire: &Repertoire,
+    options: &NarrowOptions
+) -> Result<NarrowSummary, std::io::Error> {
+    let table = reader.table();
+    let repertoire: HashMap<u64, &[Move]> = repertoire.moves.values()
+        .map(|(key, moves)| (key.polyglot_hash_with(table), moves.as_slice()))
+        .collect();
+
+    let reachable = match &options.reachability {
+        Some(reachability) => Some(crate::walk::reachable_lines_filtered(
+            reader,
+            &reachability.roots,
+            reachability.max_plies,
+            |hash, entries| narrow_position(&repertoire, hash, entries, options.missing_weight, &mut NarrowSummary::default())
+        )?),
+        None => None
+    };
+
+    // Covered positions missing from the book only get entries if missing moves are added.
+    let mut missing: Vec<u64> = match options.missing_weight {
+        Some(_) => repertoire.keys().copied().collect(),
+        None => Vec::new()
+    };
+    missing.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut summary = NarrowSummary::default();
+    let mut positions = reader.positions()?;
+    let mut next = positions.next().transpose()?;
+    loop {
+        while missing.last().is_some_and(|&hash| next.as_ref().is_some_and(|(key, _)| *key == hash)) {
+            missing.pop();
+        }
+        let (key, mut entries) = match (missing.last(), next.take()) {
+            (Some(&hash), Some(position)) if hash < position.0 => {
+                next = Some(position);
+                (missing.pop().unwrap(), Vec::new())
+            }
+            (_, Some(position)) => {
+                next = positions.next().transpose()?;
+                position
+            }
+            (Some(_), None) => (missing.pop().unwrap(), Vec::new()),
+            (None, None) => break
+        };
+
+        narrow_position(&repertoire, key, &mut entries, options.missing_weight, &mut summary);
+        if reachable.as_ref().is_some_and(|reachable| !reachable.contains_key(&key)) {
+            summary.removed_unreachable += entries.len();
+            continue;
+        }
+        let mut entries: Vec<_> = entries.into_iter().map(|entry| (key, entry)).collect();
+        sort_entries(&mut entries);
+        for (key, entry) in &entries {
+            writer.write(*key, entry)?;
+        }
+        summary.kept_entries += entries.len();
+    }
+    Ok(summary)
+}
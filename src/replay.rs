@@ -0,0 +1,84 @@
+//! Looking up the book entries for every position of a game.
+
+use std::io::{Read, Seek};
+use std::str::FromStr;
+
+use crate::board::Board;
+use crate::{Move, PolyglotEntry, PolyglotReader};
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ReplayOptions {
+    /// Stops after the first position whose played move isn't in the book.
+    pub stop_out_of_book: bool
+}
+
+/// A position of a replayed game and its book entries.
+#[derive(Debug, Clone)]
+pub struct ReplayedPosition {
+    /// The number of moves played before this position.
+    pub ply: usize,
+    pub hash: u64,
+    pub entries: Vec<PolyglotEntry>,
+    /// The move played from this position in Polyglot's castling notation, or `None` for the final position.
+    pub played: Option<Move>,
+    /// Whether the played move is one of the position's book entries.
+    pub book_move: bool
+}
+
+/// Replays UCI moves from `fen`, or the start position if it's `None`, and looks up every position on the way,
+/// including the final one. Castling may be written either as the king moving two squares or taking its rook.
+/// Fails if the FEN is invalid or a move can't be parsed or is illegal.
+pub fn replay<I: Seek + Read, M: IntoIterator<Item = S>, S: AsRef<str>>(
+    reader: &mut PolyglotReader<I>,
+    fen: Option<&str>,
+    moves: M,
+    options: &ReplayOptions
+) -> Result<Vec<ReplayedPosition>, std::io::Error> {
+    let mut board = match fen {
+        Some(fen) => Board::from_str(fen).map_err(|_| invalid_input(format!("Invalid FEN '{}'", fen)))?,
+        None => Board::default()
+    };
+    let mut positions = Vec::new();
+    let mut moves = moves.into_iter();
+    loop {
+        let key = board.key();
+        let hash = key.polyglot_hash_with(reader.table());
+        let entries = reader.get_hash(hash)?;
+        let ply = positions.len();
+        let uci = match moves.next() {
+            Some(uci) => uci,
+            None => {
+                positions.push(ReplayedPosition {
+                    ply,
+                    hash,
+                    entries,
+                    played: None,
+                    book_move: false
+                });
+                return Ok(positions);
+            }
+        };
+        let uci = uci.as_ref();
+        let mv = Move::from_str(uci)
+            .map_err(|_| invalid_input(format!("Move {}: invalid move '{}'", ply + 1, uci)))?
+            .from_standard_castling(&key);
+        let next = board.play(mv)
+            .ok_or_else(|| invalid_input(format!("Move {}: illegal move '{}'", ply + 1, uci)))?;
+        let book_move = entries.iter().any(|entry| entry.mv == mv);
+        positions.push(ReplayedPosition {
+            ply,
+            hash,
+            entries,
+            played: Some(mv),
+            book_move
+        });
+        if options.stop_out_of_book && !book_move {
+            return Ok(positions);
+        }
+        board = next;
+    }
+}
+
+fn invalid_input(message: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidInput, message)
+}
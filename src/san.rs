@@ -0,0 +1,58 @@
+use crate::board::Board;
+use crate::{Move, PieceType, PolyglotKey};
+
+impl Move {
+    /// Formats the move in Standard Algebraic Notation for `position`, accepting both Polyglot and standard castling.
+    /// Only pieces that could legally make the same move are disambiguated from, and checks and mates are marked.
+    /// Returns `None` if the move is illegal or `position` isn't one `Board::from_key` accepts.
+    pub fn to_san(self, position: &PolyglotKey) -> Option<String> {
+        let board = Board::from_key(position)?;
+        let after = board.play(self)?;
+        let piece = board.piece_on(self.source())?;
+        let standard = self.to_standard_castling(position);
+
+        let mut san = String::new();
+        if piece.piece_type == PieceType::King && self.source().file().abs_diff(standard.dest().file()) == 2 {
+            san.push_str(if standard.dest().file() == 6 { "O-O" } else { "O-O-O" });
+        } else if piece.piece_type == PieceType::Pawn {
+            if self.source().file() != self.dest().file() {
+                san.push(self.source().to_string().remove(0));
+                san.push('x');
+            }
+            san.push_str(&self.dest().to_string());
+            if let Some(promotion) = self.promotion() {
+                san.push('=');
+                san.push(promotion.to_char().to_ascii_uppercase());
+            }
+        } else {
+            san.push(piece.piece_type.to_char().to_ascii_uppercase());
+            let rivals: Vec<_> = board.legal_moves().into_iter()
+                .filter(|rival| {
+                    rival.dest() == self.dest() &&
+                        rival.source() != self.source() &&
+                        board.piece_on(rival.source()).is_some_and(|rival| rival.piece_type == piece.piece_type)
+                })
+                .map(Move::source)
+                .collect();
+            if !rivals.is_empty() {
+                let source = self.source().to_string();
+                if rivals.iter().all(|rival| rival.file() != self.source().file()) {
+                    san.push_str(&source[0..1]);
+                } else if rivals.iter().all(|rival| rival.rank() != self.source().rank()) {
+                    san.push_str(&source[1..2]);
+                } else {
+                    san.push_str(&source);
+                }
+            }
+            if board.piece_on(self.dest()).is_some() {
+                san.push('x');
+            }
+            san.push_str(&self.dest().to_string());
+        }
+
+        if after.in_check() {
+            san.push(if after.legal_moves().is_empty() { '#' } else { '+' });
+        }
+        Some(san)
+    }
+}
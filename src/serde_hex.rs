@@ -0,0 +1,14 @@
+//! Serializes `u64` hashes as 16 digit hex strings, since JavaScript numbers can't represent every 64-bit integer.
+//! Use with `#[serde(with = "chess_polyglot_reader::serde_hex")]`.
+
+use serde::{Deserialize, Deserializer, Serializer};
+use serde::de::Error;
+
+pub fn serialize<S: Serializer>(hash: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("{:016x}", hash))
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let hash = String::deserialize(deserializer)?;
+    crate::interchange::parse_hash(&hash).ok_or_else(|| D::Error::custom(format!("invalid hash '{}'", hash)))
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::Error;
+
+use crate::{CastleRights, Move, Piece, PolyglotKey, Side, Square};
+use crate::variant::VariantState;
+
+impl Serialize for Square {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Square {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+/// Moves are serialized in UCI notation, keeping Polyglot's king-takes-rook castling.
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(D::Error::custom)
+    }
+}
+
+#[derive(Serialize)]
+struct SerializeKey<'a> {
+    pieces: &'a [Piece],
+    white_castle: CastleRights,
+    black_castle: CastleRights,
+    en_passant_file: Option<usize>,
+    turn: Side,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    variant: Option<VariantState>,
+    #[serde(with = "crate::serde_hex")]
+    hash: u64
+}
+
+#[derive(Deserialize)]
+struct DeserializeKey {
+    pieces: Vec<Piece>,
+    white_castle: CastleRights,
+    black_castle: CastleRights,
+    en_passant_file: Option<usize>,
+    turn: Side,
+    #[serde(default)]
+    variant: Option<VariantState>,
+    #[serde(default)]
+    hash: Option<String>
+}
+
+/// Keys are serialized with their Polyglot hash as a hex string.
+/// The hash is optional when deserializing, but must match the position if present.
+impl Serialize for PolyglotKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializeKey {
+            pieces: &self.pieces,
+            white_castle: self.white_castle,
+            black_castle: self.black_castle,
+            en_passant_file: self.en_passant_file,
+            turn: self.turn,
+            variant: self.variant,
+            hash: self.polyglot_hash()
+        }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PolyglotKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = DeserializeKey::deserialize(deserializer)?;
+        if key.en_passant_file.is_some_and(|file| file >= 8) {
+            return Err(D::Error::custom("invalid en passant file"));
+        }
+        let polyglot_key = PolyglotKey {
+            pieces: key.pieces,
+            white_castle: key.white_castle,
+            black_castle: key.black_castle,
+            en_passant_file: key.en_passant_file,
+            turn: key.turn,
+            variant: key.variant
+        };
+        if let Some(hash) = key.hash {
+            let hash = crate::interchange::parse_hash(&hash)
+                .ok_or_else(|| D::Error::custom(format!("invalid hash '{}'", hash)))?;
+            if hash != polyglot_key.polyglot_hash() {
+                return Err(D::Error::custom("hash doesn't match the position"));
+            }
+        }
+        Ok(polyglot_key)
+    }
+}
@@ -0,0 +1,193 @@
+//! Building books too large to sort in memory, by spilling sorted runs to temporary files and merging them.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{sort_entries, PolyglotEntry, PolyglotWriter};
+
+#[derive(Debug, Clone)]
+pub struct SortOptions {
+    /// Roughly how many bytes of entries are buffered before they are spilled to a run file.
+    pub memory_limit: usize,
+    /// Where run files are created. They are removed once the sort finishes or is dropped.
+    pub temp_dir: PathBuf
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        Self {
+            memory_limit: 256 * 1024 * 1024,
+            temp_dir: std::env::temp_dir()
+        }
+    }
+}
+
+/// Collects entries in any order and writes them as a sorted book.
+/// Entries with the same key and move are combined by adding their weights, saturating at the maximum.
+/// Learn values don't add up, so combined entries have theirs reset to 0.
+/// The buffer is allocated once at `memory_limit` and spilled whenever it fills, so it never grows past it.
+#[derive(Debug)]
+pub struct ExternalSorter {
+    options: SortOptions,
+    buffer: Vec<(u64, PolyglotEntry)>,
+    capacity: usize,
+    runs: Vec<PathBuf>
+}
+
+fn combine(into: &mut PolyglotEntry, entry: &PolyglotEntry) {
+    into.weight = into.weight.saturating_add(entry.weight);
+    into.learn = 0;
+}
+
+/// Sorts by key and move and combines duplicates.
+fn sort_and_combine(entries: &mut Vec<(u64, PolyglotEntry)>) {
+    entries.sort_unstable_by_key(|(key, entry)| (*key, entry.mv.to_u16()));
+    entries.dedup_by(|(key, entry), (kept_key, kept)| {
+        let duplicate = key == kept_key && entry.mv == kept.mv;
+        if duplicate {
+            combine(kept, entry);
+        }
+        duplicate
+    });
+}
+
+struct Run {
+    reader: BufReader<File>
+}
+
+impl Run {
+    fn next(&mut self) -> Result<Option<(u64, PolyglotEntry)>, std::io::Error> {
+        let mut bytes = [0; PolyglotEntry::SIZE];
+        match self.reader.read_exact(&mut bytes) {
+            Ok(()) => {
+                let mut key = [0; 8];
+                key.copy_from_slice(&bytes[0..8]);
+                Ok(Some((u64::from_be_bytes(key), PolyglotEntry::from_bytes(&bytes[8..])?)))
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(error) => Err(error)
+        }
+    }
+}
+
+impl ExternalSorter {
+    pub fn new(options: SortOptions) -> Self {
+        let capacity = (options.memory_limit / std::mem::size_of::<(u64, PolyglotEntry)>()).max(1);
+        Self {
+            options,
+            buffer: Vec::with_capacity(capacity),
+            capacity,
+            runs: Vec::new()
+        }
+    }
+
+    pub fn push(&mut self, key: u64, entry: PolyglotEntry) -> Result<(), std::io::Error> {
+        self.buffer.push((key, entry));
+        if self.buffer.len() >= self.capacity {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// The number of run files spilled so far.
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    fn spill(&mut self) -> Result<(), std::io::Error> {
+        static NEXT_RUN: AtomicUsize = AtomicUsize::new(0);
+
+        sort_and_combine(&mut self.buffer);
+        let name = format!(
+            "chess-polyglot-run-{}-{}.bin",
+            std::process::id(),
+            NEXT_RUN.fetch_add(1, Ordering::Relaxed)
+        );
+        let path = self.options.temp_dir.join(name);
+        let file = File::create(&path)?;
+        self.runs.push(path);
+        let mut writer = PolyglotWriter::new(BufWriter::new(file));
+        for (key, entry) in self.buffer.drain(..) {
+            writer.write(key, &entry)?;
+        }
+        writer.flush()
+    }
+
+    /// Merges everything pushed so far into `writer` in book order. Returns the number of entries written.
+    pub fn finish<W: Write>(mut self, writer: &mut PolyglotWriter<W>) -> Result<usize, std::io::Error> {
+        if self.runs.is_empty() {
+            sort_and_combine(&mut self.buffer);
+            sort_entries(&mut self.buffer);
+            for (key, entry) in &self.buffer {
+                writer.write(*key, entry)?;
+            }
+            return Ok(self.buffer.len());
+        }
+        if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+        self.buffer = Vec::new();
+
+        let mut runs = Vec::with_capacity(self.runs.len());
+        let mut heap = BinaryHeap::new();
+        for (index, path) in self.runs.iter().enumerate() {
+            let mut run = Run {
+                reader: BufReader::new(File::open(path)?)
+            };
+            if let Some((key, entry)) = run.next()? {
+                heap.push(Reverse((key, entry.mv.to_u16(), index, entry.weight, entry.learn)));
+            }
+            runs.push(run);
+        }
+
+        // Entries come out of the merge by key and move, so duplicates are adjacent.
+        // A key's entries are collected to put them in descending weight order.
+        let mut written = 0;
+        let mut position: Vec<(u64, PolyglotEntry)> = Vec::new();
+        while let Some(Reverse((key, mv, index, weight, learn))) = heap.pop() {
+            if let Some((next_key, next)) = runs[index].next()? {
+                heap.push(Reverse((next_key, next.mv.to_u16(), index, next.weight, next.learn)));
+            }
+            let entry = PolyglotEntry {
+                mv: crate::Move::from_u16(mv),
+                weight,
+                learn
+            };
+            match position.last_mut() {
+                Some((last_key, last)) if *last_key == key && last.mv == entry.mv => combine(last, &entry),
+                Some((last_key, _)) if *last_key != key => {
+                    written += write_position(&mut position, writer)?;
+                    position.push((key, entry));
+                }
+                _ => position.push((key, entry))
+            }
+        }
+        written += write_position(&mut position, writer)?;
+        Ok(written)
+    }
+}
+
+fn write_position<W: Write>(
+    position: &mut Vec<(u64, PolyglotEntry)>,
+    writer: &mut PolyglotWriter<W>
+) -> Result<usize, std::io::Error> {
+    sort_entries(position);
+    for (key, entry) in position.iter() {
+        writer.write(*key, entry)?;
+    }
+    let written = position.len();
+    position.clear();
+    Ok(written)
+}
+
+impl Drop for ExternalSorter {
+    fn drop(&mut self) {
+        for path in &self.runs {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Win/draw/loss statistics kept in a sidecar file next to a book, since entries only have room for a weight.
+//!
+//! The sidecar holds 26-byte big-endian records sorted by key and then move:
+//! the key, the move in Polyglot's encoding, and the game, win, draw and loss counts.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::build::MoveStats;
+use crate::keys::KeyTable;
+use crate::{Move, PolyglotEntry, PolyglotKey, PolyglotReader};
+
+const RECORD_SIZE: usize = 26;
+
+/// Where the sidecar for the book at `book` goes: the same path with a `stats` extension.
+pub fn sidecar_path(book: &Path) -> PathBuf {
+    book.with_extension("stats")
+}
+
+#[derive(Debug)]
+pub struct StatsWriter<W> {
+    inner: W,
+    last: Option<(u64, u16)>,
+    len: u64
+}
+
+impl<W: Write> StatsWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            last: None,
+            len: 0
+        }
+    }
+    /// Appends a record. Records must be written in ascending key and move order, with no duplicates.
+    pub fn write(&mut self, key: u64, mv: Move, stats: &MoveStats) -> Result<(), std::io::Error> {
+        let order = (key, mv.to_u16());
+        if self.last.is_some_and(|last| order <= last) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Key {:016x} move {} written out of order", key, mv)
+            ));
+        }
+        let mut record = [0; RECORD_SIZE];
+        record[0..8].copy_from_slice(&key.to_be_bytes());
+        record[8..10].copy_from_slice(&order.1.to_be_bytes());
+        record[10..14].copy_from_slice(&stats.games.to_be_bytes());
+        record[14..18].copy_from_slice(&stats.wins.to_be_bytes());
+        record[18..22].copy_from_slice(&stats.draws.to_be_bytes());
+        record[22..26].copy_from_slice(&stats.losses.to_be_bytes());
+        self.inner.write_all(&record)?;
+        self.last = Some(order);
+        self.len += 1;
+        Ok(())
+    }
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.inner.flush()
+    }
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+fn read_record(bytes: &[u8]) -> Result<(u64, Move, MoveStats), std::io::Error> {
+    let u32_at = |i: usize| {
+        let mut value = [0; 4];
+        value.copy_from_slice(&bytes[i..i + 4]);
+        u32::from_be_bytes(value)
+    };
+    let mut key = [0; 8];
+    key.copy_from_slice(&bytes[0..8]);
+    let mut mv = [0; 2];
+    mv.copy_from_slice(&bytes[8..10]);
+    let mv = u16::from_be_bytes(mv);
+    let mv = Move::try_from_u16(mv).ok_or_else(|| std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("Invalid move {:#06x}", mv)
+    ))?;
+    Ok((u64::from_be_bytes(key), mv, MoveStats {
+        games: u32_at(10),
+        wins: u32_at(14),
+        draws: u32_at(18),
+        losses: u32_at(22)
+    }))
+}
+
+#[derive(Debug)]
+pub struct StatsReader<I> {
+    inner: I,
+    len: u64,
+    table: Box<KeyTable>
+}
+
+impl<I: Seek + Read> StatsReader<I> {
+    pub fn new(inner: I) -> Result<Self, std::io::Error> {
+        Self::with_table(inner, KeyTable::STANDARD)
+    }
+    /// Opens a sidecar keyed with a custom table, for `get` to hash positions with.
+    pub fn with_table(inner: I, table: KeyTable) -> Result<Self, std::io::Error> {
+        let mut inner = inner;
+        let size = inner.seek(SeekFrom::End(0))?;
+        if size % RECORD_SIZE as u64 != 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Sidecar size {} isn't a multiple of {}", size, RECORD_SIZE)
+            ));
+        }
+        Ok(Self {
+            len: size / RECORD_SIZE as u64,
+            inner,
+            table: Box::new(table)
+        })
+    }
+    pub fn table(&self) -> &KeyTable {
+        &self.table
+    }
+    pub fn get(&mut self, key: &PolyglotKey) -> Result<Vec<(Move, MoveStats)>, std::io::Error> {
+        self.get_hash(key.polyglot_hash_with(&self.table))
+    }
+    /// The statistics of every move recorded for the position, in move order.
+    pub fn get_hash(&mut self, hash: u64) -> Result<Vec<(Move, MoveStats)>, std::io::Error> {
+        let mut left = 0;
+        let mut right = self.len;
+        while left < right {
+            let middle = (left + right) / 2;
+            self.inner.seek(SeekFrom::Start(middle * RECORD_SIZE as u64))?;
+            let mut key = [0; 8];
+            self.inner.read_exact(&mut key)?;
+            if u64::from_be_bytes(key) < hash {
+                left = middle + 1;
+            } else {
+                right = middle;
+            }
+        }
+
+        let mut stats = Vec::new();
+        self.inner.seek(SeekFrom::Start(left * RECORD_SIZE as u64))?;
+        let mut record = [0; RECORD_SIZE];
+        for _ in left..self.len {
+            self.inner.read_exact(&mut record)?;
+            let (key, mv, move_stats) = read_record(&record)?;
+            if key != hash {
+                break;
+            }
+            stats.push((mv, move_stats));
+        }
+        Ok(stats)
+    }
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// A book entry with the statistics recorded for its move, if the sidecar has any.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct StatsEntry {
+    pub entry: PolyglotEntry,
+    pub stats: Option<MoveStats>
+}
+
+impl std::fmt::Display for StatsEntry {
+    /// Formats as `e2e4 (3): 3 games, +1 =1 -1, 50.0%`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.entry.mv, self.entry.weight)?;
+        match &self.stats {
+            Some(stats) => write!(f, ": {}", stats),
+            None => Ok(())
+        }
+    }
+}
+
+/// A book read together with its sidecar.
+#[derive(Debug)]
+pub struct BookStats<I, J> {
+    pub book: PolyglotReader<I>,
+    pub stats: StatsReader<J>
+}
+
+impl<I: Seek + Read, J: Seek + Read> BookStats<I, J> {
+    pub fn new(book: PolyglotReader<I>, stats: StatsReader<J>) -> Self {
+        Self {
+            book,
+            stats
+        }
+    }
+    pub fn get(&mut self, key: &PolyglotKey) -> Result<Vec<StatsEntry>, std::io::Error> {
+        self.get_hash(key.polyglot_hash_with(self.book.table()))
+    }
+    /// The book's entries in book order, each joined with the sidecar's statistics for its move.
+    pub fn get_hash(&mut self, hash: u64) -> Result<Vec<StatsEntry>, std::io::Error> {
+        let stats = self.stats.get_hash(hash)?;
+        Ok(self.book.get_hash(hash)?.into_iter().map(|entry| StatsEntry {
+            entry,
+            stats: stats.iter().find(|(mv, _)| *mv == entry.mv).map(|(_, stats)| *stats)
+        }).collect())
+    }
+}
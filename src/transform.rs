@@ -0,0 +1,173 @@
+use std::io::{Read, Seek, Write};
+
+use crate::board::Board;
+use crate::{sort_entries, PolyglotEntry, PolyglotReader, PolyglotWriter};
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct MirrorSummary {
+    pub mirrored_positions: usize,
+    pub mirrored_entries: usize,
+    /// Positions that aren't reachable from the roots can't be reconstructed, so they are dropped.
+    pub skipped_positions: usize,
+    pub skipped_entries: usize
+}
+
+/// Writes the colour-mirrored version of every book position reachable from `roots` to `writer`.
+/// Each position has its ranks flipped and its colours, castling rights and side to move swapped,
+/// turning a White repertoire into a Black one and vice versa.
+pub fn mirror<I: Seek + Read, W: Write>(
+    reader: &mut PolyglotReader<I>,
+    writer: &mut PolyglotWriter<W>,
+    roots: &[Board]
+) -> Result<MirrorSummary, std::io::Error> {
+    let lines = crate::walk::reachable_lines(reader, roots, None)?;
+    let table = reader.table().clone();
+
+    let mut summary = MirrorSummary::default();
+    let mut mirrored = Vec::new();
+    for position in reader.positions()? {
+        let (key, entries) = position?;
+        let line = match lines.get(&key) {
+            Some(line) => line,
+            None => {
+                summary.skipped_positions += 1;
+                summary.skipped_entries += entries.len();
+                continue;
+            }
+        };
+        let mirrored_key = line.board.key().mirrored().polyglot_hash_with(&table);
+        summary.mirrored_positions += 1;
+        summary.mirrored_entries += entries.len();
+        mirrored.extend(entries.into_iter().map(|mut entry| {
+            entry.mv = entry.mv.mirrored();
+            (mirrored_key, entry)
+        }));
+    }
+
+    sort_entries(&mut mirrored);
+    for (key, entry) in &mirrored {
+        writer.write(*key, entry)?;
+    }
+    Ok(summary)
+}
+
+/// A rewrite of the weights of one position's entries.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Rescale {
+    /// Scales weights in proportion so they sum to exactly this, rounding by largest remainder.
+    /// Positions whose weights are all 0 are left alone.
+    SumTo(u16),
+    /// Replaces each weight `w` with `scale * ln(1 + w)`, rounded, flattening the gap between popular and rare moves.
+    Log { scale: f64 },
+    /// Clamps every weight to `min..=max`. `min` must not be above `max`.
+    Clamp { min: u16, max: u16 },
+    /// Converts weights to whole percentages of the position's total, as `SumTo(100)` does.
+    Percentages,
+    /// Gives the heaviest move `share` (between 0 and 1) of the position's total weight and splits the rest
+    /// between the other moves in proportion to their weights, or evenly if they all have none.
+    /// Totals too large for a weight are first scaled down to 65535 so every move keeps its share.
+    /// Positions with a single move are left alone.
+    BestMoveShare { share: f64 }
+}
+
+/// Splits `total` in proportion to `parts`, rounding by largest remainder so the result sums to exactly `total`.
+fn apportion(parts: &[u64], total: u64) -> Vec<u64> {
+    let sum: u64 = parts.iter().sum();
+    if sum == 0 {
+        return parts.to_vec();
+    }
+    let mut shares: Vec<u64> = parts.iter().map(|&part| (part as u128 * total as u128 / sum as u128) as u64).collect();
+    let mut by_remainder: Vec<usize> = (0..parts.len()).collect();
+    by_remainder.sort_by_key(|&i| std::cmp::Reverse(parts[i] as u128 * total as u128 % sum as u128));
+    let remaining = total - shares.iter().sum::<u64>();
+    for &i in by_remainder.iter().take(remaining as usize) {
+        shares[i] += 1;
+    }
+    shares
+}
+
+impl Rescale {
+    /// Returns an `InvalidInput` error if this is a `Clamp` with its minimum above its maximum.
+    pub fn validate(&self) -> Result<(), std::io::Error> {
+        match *self {
+            Rescale::Clamp { min, max } if min > max => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Clamp minimum {} is above its maximum {}", min, max)
+            )),
+            _ => Ok(())
+        }
+    }
+
+    /// Rewrites the weights of one position's entries. Fails, changing nothing, if `validate` does.
+    pub fn apply(&self, entries: &mut [PolyglotEntry]) -> Result<(), std::io::Error> {
+        self.validate()?;
+        let weights: Vec<u64> = entries.iter().map(|entry| entry.weight as u64).collect();
+        let weights = match *self {
+            Rescale::SumTo(target) => apportion(&weights, target as u64),
+            Rescale::Percentages => apportion(&weights, 100),
+            Rescale::Log { scale } => weights.iter().map(|&weight| (scale * (weight as f64).ln_1p()).round() as u64).collect(),
+            Rescale::Clamp { min, max } => weights.iter().map(|&weight| weight.clamp(min as u64, max as u64)).collect(),
+            Rescale::BestMoveShare { share } => {
+                let best = match (0..weights.len()).max_by_key(|&i| (weights[i], std::cmp::Reverse(i))) {
+                    Some(best) if weights.len() > 1 => best,
+                    _ => return Ok(())
+                };
+                let total = weights.iter().sum::<u64>().min(u16::MAX as u64);
+                let best_weight = (total as f64 * share.clamp(0.0, 1.0)).round() as u64;
+                let mut others: Vec<u64> = weights.iter().enumerate()
+                    .filter(|&(i, _)| i != best)
+                    .map(|(_, &weight)| weight)
+                    .collect();
+                if others.iter().all(|&weight| weight == 0) {
+                    others.iter_mut().for_each(|weight| *weight = 1);
+                }
+                let mut others = apportion(&others, total - best_weight).into_iter();
+                (0..weights.len()).map(|i| if i == best { best_weight } else { others.next().unwrap() }).collect()
+            }
+        };
+        for (entry, weight) in entries.iter_mut().zip(weights) {
+            entry.weight = weight.min(u16::MAX as u64) as u16;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct RescaleSummary {
+    pub positions: usize,
+    pub entries: usize,
+    /// Entries whose weight ended up different from the original.
+    pub changed_entries: usize
+}
+
+/// Copies `reader` into `writer` one position at a time, applying each of `rescales` in order to every position's weights.
+/// Entries within a position are put back in descending weight order.
+/// Returns an `InvalidInput` error before writing anything if a `Clamp` has its minimum above its maximum.
+pub fn rescale<I: Seek + Read, W: Write>(
+    reader: &mut PolyglotReader<I>,
+    writer: &mut PolyglotWriter<W>,
+    rescales: &[Rescale]
+) -> Result<RescaleSummary, std::io::Error> {
+    for rescale in rescales {
+        rescale.validate()?;
+    }
+
+    let mut summary = RescaleSummary::default();
+    for position in reader.positions()? {
+        let (key, mut entries) = position?;
+        let original: Vec<u16> = entries.iter().map(|entry| entry.weight).collect();
+        for rescale in rescales {
+            rescale.apply(&mut entries)?;
+        }
+        summary.positions += 1;
+        summary.entries += entries.len();
+        summary.changed_entries += entries.iter().zip(original).filter(|(entry, weight)| entry.weight != *weight).count();
+
+        let mut entries: Vec<_> = entries.into_iter().map(|entry| (key, entry)).collect();
+        sort_entries(&mut entries);
+        for (key, entry) in &entries {
+            writer.write(*key, entry)?;
+        }
+    }
+    Ok(summary)
+}
@@ -0,0 +1,270 @@
+//! Extra key state for chess variants whose positions standard Polyglot keys can't tell apart.
+//!
+//! Polyglot only defines random numbers for standard chess, so the variant terms use the Zobrist masks of
+//! [shakmaty](https://crates.io/crates/shakmaty) 0.30, truncated to their low 64 bits as its `Zobrist64` does.
+//! Since shakmaty's standard terms are Polyglot's, keys hash exactly as shakmaty's `Zobrist64` hashes the same position,
+//! and keys without variant state hash exactly as standard Polyglot keys do.
+//! King of the Hill positions are keyed that way too, since its rules need no extra state.
+//! `Board` and move generation remain standard chess only.
+
+use crate::{PieceType, Side};
+
+/// Indexed by Polyglot piece kind (black pawn, white pawn, black knight and so on up to white queen),
+/// then by bit of the count held. A count is hashed by XORing the numbers of its set bits, so counts wrap at 128.
+pub const RANDOM_POCKET: [u64; 2 * 5 * 7] = [
+    0xB262E9F9D6123320,
+    0x91533947CDAA8BEC,
+    0xA13B56B45723A3D4,
+    0x9A35CCE29CA3AC75,
+    0x2716940E1D4F28D7,
+    0x7447209CFB793066,
+    0x5CF91D8AE6402E1A,
+    0x9B253F89247C4C1D,
+    0x1E701E2A73F9DC4B,
+    0xCDF351B289AA5A84,
+    0x2E4E118FC45FDC0D,
+    0x80247D70885AD5CE,
+    0x0A99DCCFCE316CA0,
+    0xB5553435DAE76840,
+    0x4625588D38487AC5,
+    0xE42EC6191353E3BD,
+    0x478E6CC8F6B2DADA,
+    0x1726FC948B994B87,
+    0xFB9D2E5A66B46741,
+    0x7F668E401FFE9E6F,
+    0xEE4D6FE11C46A236,
+    0xEE562004D5D14158,
+    0x551B5FA3EC7166A2,
+    0x2DBB493C6E9FEC06,
+    0xF06B4C65F4BB14A1,
+    0x5F0B44D98013ACB9,
+    0xCE7DBAFA734BBA8A,
+    0xE009C0E355A77913,
+    0x006CB70064259959,
+    0x33535A7C4DEF1B24,
+    0x479E792F8171FC29,
+    0x656A6E71DE970975,
+    0xCADA3E48618A1C2B,
+    0xB37AD7262DB9C99E,
+    0x85AE25402A311D5D,
+    0x21918F473CB6DECF,
+    0xDCF11E80DC14763F,
+    0x7AC21357500FB0C6,
+    0x28ABE0A3761E326C,
+    0x30B8E3DA17D34C6E,
+    0xD999D38FFA5D771E,
+    0x8A7E0D1367D70B28,
+    0x3DE4E82D52DBB44C,
+    0xB1C8499674464C21,
+    0xF1C1853CC6827B84,
+    0x51F97ED3BA004FB0,
+    0x00DA9EDE878E3E98,
+    0x3CD0FD658E1CDB12,
+    0xAC2940B688A1D0F9,
+    0x9157BFE7AC071796,
+    0xADDA94B21EDD779A,
+    0x6F555CF7856F0D63,
+    0x5B2A5B2788ADC947,
+    0x500C782C8C562A42,
+    0x20F8B3F7059D8884,
+    0x79C890ED3E95F3F4,
+    0xE51ACB5B336DB0DF,
+    0xCF7517FBDCB16174,
+    0xDFE901ABA4A2CED3,
+    0x24BFD4B72C8852EB,
+    0xF085BCD9711883D4,
+    0x41B71908A3D86274,
+    0x6D604CC0A2DF1A69,
+    0xE64DBD474DDCF8CA,
+    0xA94966FBF7F270D5,
+    0x2473B4E6AD9FAA9A,
+    0x98ABDF9FA4B487E6,
+    0x75FA1ECB0717029A,
+    0xF6053757646A08BA,
+    0x060E2788D99813AA,
+];
+/// Indexed by square, for each piece promoted from a pawn, which goes back to the pocket as a pawn when captured.
+pub const RANDOM_PROMOTED: [u64; 64] = [
+    0x2F9900CC2B7A19CA,
+    0xF75235BEB01886D3,
+    0x8AE7E29889AC9964,
+    0xAD30091CE7CB4204,
+    0xAAE118773DDD4E4D,
+    0x8EC514CE4736AA07,
+    0x26A412BD8CEF4F15,
+    0x1BDCE26BD9AF059F,
+    0xEA5F4ADE5ACC0516,
+    0x69AB7EBC07650565,
+    0x3E655F895A188A1C,
+    0xF394F6882A114D65,
+    0x3173CFA2BE5BD4D3,
+    0x434D20D2CA00AE71,
+    0x3BA297F73D338C93,
+    0x099BA1B0205A5EA5,
+    0xC49F050B5E1C5653,
+    0xE14EEC50A9C690E8,
+    0x2571CC79F4CE0169,
+    0xDE0F98D6002F4323,
+    0x0682220B02E5C3E8,
+    0xCB900D3A6B38C39D,
+    0x24620FBF09D50D66,
+    0x0F40A9B2781A119D,
+    0x83C6980DF0D04932,
+    0xAB6F9AF720CB5DF4,
+    0x1C906974166EE8D4,
+    0x9C1BA3DB0784EBDA,
+    0x81A19098D16AA929,
+    0xFCE56173C63CCEFD,
+    0x43CB7AA20C6209C2,
+    0x7E96E2AE86924BAB,
+    0x01860725034B0FEF,
+    0xF74D369066EC4E96,
+    0x1AE9962C6E0D1232,
+    0x5D66FA465CCFC560,
+    0xE9C13AE1FC36AFAA,
+    0xCAEC4035FB840BE4,
+    0x839D28ADAFAD0F8F,
+    0xE4703B6E30422003,
+    0x1E2FD5B2827D5E43,
+    0x96F1E8D8B94BD960,
+    0x90F2075C3F43960C,
+    0xC48E0774C4F9134F,
+    0xF17E5F6A2CB000C7,
+    0x6248409BF55A4925,
+    0x967BD94EB30505CC,
+    0xE91E89853F9E844F,
+    0xB841038E24193F08,
+    0x46F3B25CAE82A6CC,
+    0x3E97E042449E3ED5,
+    0x868A166AF46DCBD2,
+    0xF71BE788B3FD1A7A,
+    0xCB6D65410533CC37,
+    0x7E30D70559EFAEDC,
+    0x32DB0F5CA18159CE,
+    0x97A9116E874228C5,
+    0x85EE68EE3A175297,
+    0x076A14170B409E2A,
+    0xBAD49D47DC95855B,
+    0x636187D94DED991E,
+    0x962E50971F09CFAB,
+    0x8F16C910D6776589,
+    0x7E3DE4BFBEF5566F,
+];
+/// Indexed by side (black first), then by bit of the number of checks given.
+pub const RANDOM_CHECKS: [u64; 4] = [
+    0x1D6DC0EE61CE803E,
+    0xC6284B653D38E96A,
+    0x803F5FB0D2F97FAE,
+    0xB183CCC9E73DF9ED,
+];
+
+/// The index of a side's numbers in tables ordered black first, as Polyglot orders piece kinds.
+fn side_index(side: Side) -> usize {
+    match side {
+        Side::White => 1,
+        Side::Black => 0
+    }
+}
+
+/// XORs together the numbers of the set bits of `count`.
+fn count_hash(randoms: &[u64], count: u8) -> u64 {
+    randoms.iter().enumerate()
+        .filter(|&(bit, _)| count & (1 << bit) != 0)
+        .fold(0, |hash, (_, random)| hash ^ random)
+}
+
+/// The pieces each side holds in hand in Crazyhouse.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pockets {
+    /// Counts by piece type from pawn to queen.
+    pub white: [u8; 5],
+    pub black: [u8; 5]
+}
+
+impl Pockets {
+    /// The number of `piece_type` held by `side`. Kings are never in hand.
+    pub fn count(&self, side: Side, piece_type: PieceType) -> u8 {
+        match piece_type {
+            PieceType::King => 0,
+            _ => self.side(side)[piece_type.index()]
+        }
+    }
+
+    /// Adds one `piece_type` to the pocket of `side`. Returns false for kings.
+    pub fn add(&mut self, side: Side, piece_type: PieceType) -> bool {
+        if piece_type == PieceType::King {
+            return false;
+        }
+        let pocket = match side {
+            Side::White => &mut self.white,
+            Side::Black => &mut self.black
+        };
+        pocket[piece_type.index()] = pocket[piece_type.index()].saturating_add(1);
+        true
+    }
+
+    fn side(&self, side: Side) -> &[u8; 5] {
+        match side {
+            Side::White => &self.white,
+            Side::Black => &self.black
+        }
+    }
+
+    pub fn polyglot_hash(&self) -> u64 {
+        let mut hash = 0;
+        for side in [Side::White, Side::Black] {
+            for (piece_index, &count) in self.side(side).iter().enumerate() {
+                let kind = piece_index * 2 + side_index(side);
+                hash ^= count_hash(&RANDOM_POCKET[kind * 7..kind * 7 + 7], count);
+            }
+        }
+        hash
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VariantState {
+    Crazyhouse {
+        pockets: Pockets,
+        /// The squares of promoted pieces, as a bitboard with bit `i` for the square with index `i`.
+        promoted: u64
+    },
+    /// The number of checks each side has given, from 0 to 3.
+    ThreeCheck { white_checks: u8, black_checks: u8 }
+}
+
+impl VariantState {
+    /// The term XORed into the standard Polyglot hash. Empty pockets, no promoted pieces and no checks given add nothing.
+    pub fn polyglot_hash(&self) -> u64 {
+        match *self {
+            VariantState::Crazyhouse { pockets, promoted } => {
+                (0..64)
+                    .filter(|&index| promoted & (1 << index) != 0)
+                    .fold(pockets.polyglot_hash(), |hash, index| hash ^ RANDOM_PROMOTED[index])
+            }
+            VariantState::ThreeCheck { white_checks, black_checks } => {
+                let checks = |side| &RANDOM_CHECKS[side_index(side) * 2..side_index(side) * 2 + 2];
+                count_hash(checks(Side::White), white_checks) ^ count_hash(checks(Side::Black), black_checks)
+            }
+        }
+    }
+
+    /// The same state with the sides swapped, to go with `PolyglotKey::mirrored`.
+    pub fn mirrored(&self) -> VariantState {
+        match *self {
+            VariantState::Crazyhouse { pockets, promoted } => VariantState::Crazyhouse {
+                pockets: Pockets {
+                    white: pockets.black,
+                    black: pockets.white
+                },
+                promoted: promoted.swap_bytes()
+            },
+            VariantState::ThreeCheck { white_checks, black_checks } => VariantState::ThreeCheck {
+                white_checks: black_checks,
+                black_checks: white_checks
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map::Entry;
+use std::io::{Read, Seek};
+
+use crate::board::Board;
+use crate::{Move, PolyglotEntry, PolyglotReader};
+
+/// A position reached by playing book moves from a root position.
+#[derive(Debug, Clone)]
+pub struct BookLine {
+    pub board: Board,
+    pub moves: Vec<Move>
+}
+
+impl BookLine {
+    pub fn fen(&self) -> String {
+        self.board.to_string()
+    }
+    pub fn ply(&self) -> usize {
+        self.moves.len()
+    }
+}
+
+/// Converts a book move into a legal `chess::ChessMove` for `board`.
+/// Polyglot encodes castling as the king capturing its own rook, so those moves are
+/// translated to the king's destination square. Returns `None` if the move is illegal.
+#[cfg(feature = "chess_lib_helpers")]
+pub fn to_chess_move(board: &chess::Board, mv: Move) -> Option<chess::ChessMove> {
+    let mut chess_move: chess::ChessMove = mv.into();
+    let source = chess_move.get_source();
+    let dest = chess_move.get_dest();
+    if board.piece_on(source) == Some(chess::Piece::King) &&
+        board.piece_on(dest) == Some(chess::Piece::Rook) &&
+        board.color_on(source) == board.color_on(dest) &&
+        mv.source().file() == 4 && mv.source().rank() == mv.dest().rank() {
+        let file = match mv.dest().file() {
+            0 => chess::File::C,
+            7 => chess::File::G,
+            _ => return None
+        };
+        chess_move = chess::ChessMove::new(source, chess::Square::make_square(dest.get_rank(), file), None);
+    }
+    if board.legal(chess_move) {
+        Some(chess_move)
+    } else {
+        None
+    }
+}
+
+/// Finds every position reachable from `roots` through book moves, keyed by hash under the reader's key table.
+/// Positions are visited breadth-first, so each line is a shortest path from a root.
+/// Leaf positions (reached by a book move but not in the book themselves) are included.
+/// Book moves that are illegal in the reconstructed position are skipped.
+pub fn reachable_lines<I: Seek + Read>(
+    reader: &mut PolyglotReader<I>,
+    roots: &[Board],
+    max_plies: Option<usize>
+) -> Result<HashMap<u64, BookLine>, std::io::Error> {
+    reachable_lines_filtered(reader, roots, max_plies, |_, _| {})
+}
+
+/// Like `reachable_lines`, but only follows the book moves that `filter` leaves in place.
+pub fn reachable_lines_filtered<I: Seek + Read, F: FnMut(u64, &mut Vec<PolyglotEntry>)>(
+    reader: &mut PolyglotReader<I>,
+    roots: &[Board],
+    max_plies: Option<usize>,
+    mut filter: F
+) -> Result<HashMap<u64, BookLine>, std::io::Error> {
+    let mut lines = HashMap::new();
+    let mut queue = VecDeque::new();
+    for &board in roots {
+        let hash = board.polyglot_hash_with(reader.table());
+        if let Entry::Vacant(vacant) = lines.entry(hash) {
+            vacant.insert(BookLine {
+                board,
+                moves: Vec::new()
+            });
+            queue.push_back(hash);
+        }
+    }
+
+    while let Some(hash) = queue.pop_front() {
+        let line = &lines[&hash];
+        if max_plies.is_some_and(|max_plies| line.ply() >= max_plies) {
+            continue;
+        }
+        let board = line.board;
+        let moves = line.moves.clone();
+        let mut entries = reader.get_hash(hash)?;
+        filter(hash, &mut entries);
+        for entry in entries {
+            let child = match board.play(entry.mv) {
+                Some(child) => child,
+                None => continue
+            };
+            let child_hash = child.polyglot_hash_with(reader.table());
+            if let Entry::Vacant(vacant) = lines.entry(child_hash) {
+                let mut child_moves = moves.clone();
+                child_moves.push(entry.mv);
+                vacant.insert(BookLine {
+                    board: child,
+                    moves: child_moves
+                });
+                queue.push_back(child_hash);
+            }
+        }
+    }
+    Ok(lines)
+}
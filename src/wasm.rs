@@ -0,0 +1,81 @@
+//! WebAssembly bindings for looking up positions in a book held in memory.
+//! Hashes are passed to and from JavaScript as hex strings, since JavaScript numbers can't hold them exactly.
+
+use std::io::Cursor;
+
+use wasm_bindgen::prelude::*;
+
+use crate::interchange::parse_hash;
+use crate::{PolyglotEntry, PolyglotKey, PolyglotReader};
+
+fn parse_fen(fen: &str) -> Result<PolyglotKey, JsError> {
+    fen.parse().map_err(|_| JsError::new(&format!("invalid FEN '{}'", fen)))
+}
+
+fn io_error(error: std::io::Error) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+#[wasm_bindgen]
+pub struct BookMove {
+    /// The move in UCI notation.
+    #[wasm_bindgen(getter_with_clone)]
+    pub uci: String,
+    pub weight: u16,
+    pub learn: u32
+}
+
+impl BookMove {
+    fn new(uci: String, entry: &PolyglotEntry) -> Self {
+        Self {
+            uci,
+            weight: entry.weight,
+            learn: entry.learn
+        }
+    }
+}
+
+/// A Polyglot book read from the bytes of a `.bin` file, such as a `Uint8Array`.
+#[wasm_bindgen]
+pub struct Book {
+    reader: PolyglotReader<Cursor<Vec<u8>>>
+}
+
+#[wasm_bindgen]
+impl Book {
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: Vec<u8>) -> Result<Book, JsError> {
+        Ok(Self {
+            reader: PolyglotReader::new(Cursor::new(bytes)).map_err(io_error)?
+        })
+    }
+
+    /// The book moves in the position, with castling as the king moving two squares.
+    pub fn probe(&mut self, fen: &str) -> Result<Vec<BookMove>, JsError> {
+        let key = parse_fen(fen)?;
+        let entries = self.reader.get(&key).map_err(io_error)?;
+        Ok(entries.iter().map(|entry| {
+            BookMove::new(entry.mv.to_standard_castling(&key).to_string(), entry)
+        }).collect())
+    }
+
+    /// The book moves for a hex hash. Castling is left as the king taking its own rook,
+    /// since the position isn't known.
+    #[wasm_bindgen(js_name = probeHash)]
+    pub fn probe_hash(&mut self, hash: &str) -> Result<Vec<BookMove>, JsError> {
+        let hash = parse_hash(hash).ok_or_else(|| JsError::new(&format!("invalid hash '{}'", hash)))?;
+        let entries = self.reader.get_hash(hash).map_err(io_error)?;
+        Ok(entries.iter().map(|entry| BookMove::new(entry.mv.to_string(), entry)).collect())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.reader.len()
+    }
+}
+
+/// The Polyglot hash of the position as a 16 digit hex string.
+#[wasm_bindgen]
+pub fn hash(fen: &str) -> Result<String, JsError> {
+    Ok(format!("{:016x}", parse_fen(fen)?.polyglot_hash()))
+}
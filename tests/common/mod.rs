@@ -0,0 +1,30 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use chess_polyglot_reader::*;
+use std::io::Cursor;
+
+/// An entry for a move in UCI notation, with no learn value.
+pub fn entry(mv: &str, weight: u16) -> PolyglotEntry {
+    PolyglotEntry {
+        mv: mv.parse().unwrap(),
+        weight,
+        learn: 0
+    }
+}
+
+/// Writes `(key, move, weight)` entries as a book, putting them in book order first.
+pub fn book_bytes(entries: &[(u64, &str, u16)]) -> Vec<u8> {
+    let mut entries: Vec<_> = entries.iter().map(|&(key, mv, weight)| (key, entry(mv, weight))).collect();
+    sort_entries(&mut entries);
+    let mut writer = PolyglotWriter::new(Vec::new());
+    for (key, entry) in &entries {
+        writer.write(*key, entry).unwrap();
+    }
+    writer.into_inner()
+}
+
+/// A book of `(key, move, weight)` entries read from memory.
+pub fn book(entries: &[(u64, &str, u16)]) -> PolyglotReader<Cursor<Vec<u8>>> {
+    PolyglotReader::new(Cursor::new(book_bytes(entries))).unwrap()
+}
@@ -0,0 +1,30 @@
+# Run with `pytest tests/python` after installing the bindings, e.g. `maturin develop`.
+import os
+
+import pytest
+
+import chess_polyglot_reader
+
+BOOK = os.path.join(os.path.dirname(__file__), "..", "..", "test-data", "test_book.bin")
+START = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+
+
+def test_hash():
+    assert chess_polyglot_reader.hash(START) == 0x463B96181691FC9C
+    with pytest.raises(ValueError):
+        chess_polyglot_reader.hash("not a fen")
+
+
+def test_probe():
+    book = chess_polyglot_reader.Book(BOOK)
+    assert len(book) > 0
+    moves = book.probe(START)
+    assert sorted(move for move, _, _ in moves) == ["c2c4", "d2d4", "e2e4"]
+    assert all(weight > 0 for _, weight, _ in moves)
+    assert book.probe_hash(0x463B96181691FC9C) == moves
+    assert book.probe("8/8/8/8/8/8/8/K6k w - - 0 1") == []
+
+
+def test_missing_book():
+    with pytest.raises(OSError):
+        chess_polyglot_reader.Book("missing.bin")
@@ -0,0 +1,95 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::board::*;
+
+const PERFT_TESTS: &[(&str, &[u64])] = &[
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", &[20, 400, 8902, 197281]),
+    ("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", &[48, 2039, 97862]),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", &[14, 191, 2812, 43238]),
+    ("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", &[6, 264, 9467]),
+    ("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8", &[44, 1486, 62379]),
+    ("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10", &[46, 2079, 89890])
+];
+
+#[test]
+fn test_perft() {
+    for &(fen, counts) in PERFT_TESTS {
+        let board: Board = fen.parse().unwrap();
+        for (depth, &count) in counts.iter().enumerate() {
+            assert_eq!(board.perft(depth + 1), count, "Perft {} of '{}'", depth + 1, fen);
+        }
+    }
+}
+
+#[test]
+fn test_fen_round_trip() {
+    let fens = [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 12 40"
+    ];
+    for fen in fens {
+        let board: Board = fen.parse().unwrap();
+        assert_eq!(board.to_string(), fen);
+        assert_eq!(board.polyglot_hash(), fen.parse::<PolyglotKey>().unwrap().polyglot_hash());
+    }
+    assert_eq!(Board::default().to_string(), fens[0]);
+    assert_eq!(Board::default().polyglot_hash(), 0x463b96181691fc9c);
+
+    for invalid in [
+        "8/8/8/8/8/8/8/8 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4K2K w - - 0 1",
+        "4k3/8/8/8/8/8/8/P3K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4R1K1 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4K3 w - - x 1",
+        "4k3/8/8/3NP3/8/8/8/4K3 w - d6 0 1",
+        "4k3/8/3n4/3pP3/8/8/8/4K3 w - d6 0 1",
+        "4k3/3n4/8/3pP3/8/8/8/4K3 w - d6 0 1",
+        "4k3/8/8/8/3pN3/8/8/4K3 b - e3 0 1"
+    ] {
+        assert!(invalid.parse::<Board>().is_err(), "Parsing '{}'", invalid);
+    }
+}
+
+#[test]
+fn test_play() {
+    let mut board = Board::default();
+    for uci in ["e2e4", "c7c5", "g1f3", "d7d6", "f1b5", "c8d7", "e1h1"] {
+        board = board.play(uci.parse().unwrap()).unwrap_or_else(|| panic!("Playing {}", uci));
+    }
+    assert_eq!(board.to_string(), "rn1qkbnr/pp1bpppp/3p4/1Bp5/4P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 3 4");
+    assert!(board.play("e1g1".parse().unwrap()).is_none());
+    assert!(!board.is_legal("d7d5".parse().unwrap()));
+    assert!(board.is_legal("d7b5".parse().unwrap()));
+
+    let castling: Board = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+    assert_eq!(castling.play("e1g1".parse().unwrap()), castling.play("e1h1".parse().unwrap()));
+    assert_eq!(castling.play("e1c1".parse().unwrap()).unwrap().to_string(), "r3k2r/8/8/8/8/8/8/2KR3R b kq - 1 1");
+
+    let en_passant: Board = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3".parse().unwrap();
+    let captured = en_passant.play("e5f6".parse().unwrap()).unwrap();
+    assert_eq!(captured.to_string(), "rnbqkbnr/ppp1p1pp/5P2/3p4/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 3");
+
+    let mate: Board = "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3".parse().unwrap();
+    assert!(mate.in_check());
+    assert!(mate.legal_moves().is_empty());
+}
+
+#[test]
+fn test_mirrored_board() {
+    let board: Board = "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3".parse().unwrap();
+    let mirrored = board.mirrored();
+    assert_eq!(mirrored.to_string(), "rnbqkbnr/pppp1ppp/8/8/3PpP2/8/PPP1P1PP/RNBQKBNR b KQkq f3 0 3");
+    assert_eq!(mirrored.polyglot_hash(), board.key().mirrored().polyglot_hash());
+    assert_eq!(mirrored.mirrored(), board);
+}
+
+#[cfg(feature = "chess_lib_helpers")]
+#[test]
+fn test_chess_board_conversion() {
+    let fen = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+    let chess_board: chess::Board = fen.parse().unwrap();
+    let board = Board::from(&chess_board);
+    assert_eq!(board.polyglot_hash(), PolyglotKey::from_board(&chess_board).polyglot_hash());
+    assert_eq!(chess::Board::from(&board), chess_board);
+    assert_eq!(board.legal_moves().len(), chess::MoveGen::new_legal(&chess_board).len());
+}
@@ -0,0 +1,152 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::build::*;
+use chess_polyglot_reader::filter::*;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+const START: u64 = 0x463b96181691fc9c;
+
+fn paths() -> Vec<PathBuf> {
+    vec![PathBuf::from("test-data/games_a.pgn"), PathBuf::from("test-data/games_b.pgn")]
+}
+
+fn build(options: &BuildOptions) -> (Vec<u8>, BuildSummary) {
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let summary = build_book(&paths(), &mut writer, options).unwrap();
+    (writer.into_inner(), summary)
+}
+
+fn key(fen: &str) -> u64 {
+    fen.parse::<PolyglotKey>().unwrap().polyglot_hash()
+}
+
+#[test]
+fn test_collect_stats() {
+    let (stats, summary) = collect_stats(&paths(), &BuildOptions::default()).unwrap();
+    assert_eq!(summary.games, 5);
+    assert_eq!(summary.skipped_games, 1);
+    assert_eq!(summary.entries, stats.len());
+
+    let find = |key: u64, mv: &str| stats.iter()
+        .find(|(stats_key, stats_move, _)| *stats_key == key && stats_move.to_string() == mv)
+        .map(|(_, _, move_stats)| *move_stats);
+    assert_eq!(find(START, "e2e4"), Some(MoveStats {
+        games: 3,
+        wins: 1,
+        draws: 1,
+        losses: 1
+    }));
+    assert_eq!(find(START, "e2e4").unwrap().weight(), 3);
+    assert_eq!(find(START, "d2d4").unwrap().losses, 1);
+    let after_e4 = key("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    assert_eq!(find(after_e4, "c7c5").unwrap().wins, 1);
+    assert_eq!(find(after_e4, "c7c5").unwrap().losses, 1);
+
+    let castling = key("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    assert_eq!(find(castling, "e1h1").unwrap().games, 1);
+    assert_eq!(find(castling, "e1h1").unwrap().weight(), 0);
+    let castled = key("r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+    assert!(find(castled, "e8a8").is_some());
+}
+
+#[test]
+fn test_build_is_deterministic() {
+    let (single, summary) = build(&BuildOptions {
+        threads: 1,
+        ..BuildOptions::default()
+    });
+    for threads in [2, 4, 0] {
+        let (parallel, parallel_summary) = build(&BuildOptions {
+            threads,
+            ..BuildOptions::default()
+        });
+        assert!(parallel == single, "Building with {} threads", threads);
+        assert_eq!(parallel_summary, summary);
+    }
+
+    let mut reader = PolyglotReader::new(Cursor::new(single)).unwrap();
+    assert_eq!(reader.len(), summary.entries);
+    let start: Vec<_> = reader.get_hash(START).unwrap().iter().map(|entry| (entry.mv.to_string(), entry.weight)).collect();
+    assert_eq!(start, vec![("e2e4".to_string(), 3), ("d2d4".to_string(), 0)]);
+}
+
+#[test]
+fn test_build_single_large_file() {
+    let games = std::fs::read_to_string("test-data/games_a.pgn").unwrap() + "\n";
+    let path = std::env::temp_dir().join(format!("chess-polyglot-large-{}.pgn", std::process::id()));
+    std::fs::write(&path, games.repeat(300)).unwrap();
+    let paths = [path.clone()];
+    let (single, single_summary) = collect_stats(&paths, &BuildOptions {
+        threads: 1,
+        ..BuildOptions::default()
+    }).unwrap();
+    let (parallel, parallel_summary) = collect_stats(&paths, &BuildOptions {
+        threads: 4,
+        ..BuildOptions::default()
+    }).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(parallel, single);
+    assert_eq!(parallel_summary, single_summary);
+    let (_, summary) = collect_stats(&[PathBuf::from("test-data/games_a.pgn")], &BuildOptions::default()).unwrap();
+    assert_eq!(single_summary.games, summary.games * 300);
+    assert_eq!(single_summary.skipped_games, summary.skipped_games * 300);
+}
+
+#[test]
+fn test_build_options() {
+    let (book, summary) = build(&BuildOptions {
+        max_plies: Some(1),
+        ..BuildOptions::default()
+    });
+    assert_eq!(summary.positions, 2);
+    assert_eq!(PolyglotReader::new(Cursor::new(book)).unwrap().len(), 3);
+
+    let (book, summary) = build(&BuildOptions {
+        min_games: 2,
+        ..BuildOptions::default()
+    });
+    let mut reader = PolyglotReader::new(Cursor::new(book)).unwrap();
+    assert_eq!(reader.len(), summary.entries);
+    assert_eq!(reader.get_hash(START).unwrap().len(), 1);
+}
+
+#[test]
+fn test_build_filters() {
+    let filtered = |filter: GameFilter| {
+        collect_stats(&paths(), &BuildOptions {
+            filter,
+            ..BuildOptions::default()
+        }).unwrap()
+    };
+    let games = |filter: GameFilter| {
+        let (_, summary) = filtered(filter);
+        (summary.games, summary.filtered_games)
+    };
+    assert_eq!(games(GameFilter { min_elo: Some(2000), ..GameFilter::default() }), (2, 4));
+    assert_eq!(games(GameFilter {
+        time_controls: vec![TimeControl::Bullet, TimeControl::Blitz],
+        ..GameFilter::default()
+    }), (2, 4));
+    assert_eq!(games(GameFilter { min_date: "2021.01.01".parse().ok(), ..GameFilter::default() }), (3, 3));
+    assert_eq!(games(GameFilter { min_plies: 10, ..GameFilter::default() }), (3, 3));
+
+    let (stats, summary) = filtered(GameFilter {
+        result: ResultFilter::Winner,
+        ..GameFilter::default()
+    });
+    assert_eq!((summary.games, summary.filtered_games, summary.skipped_games), (3, 2, 1));
+    let find = |key: u64, mv: &str| stats.iter()
+        .find(|(stats_key, stats_move, _)| *stats_key == key && stats_move.to_string() == mv)
+        .map(|(_, _, move_stats)| *move_stats);
+    assert_eq!(find(START, "e2e4"), Some(MoveStats {
+        games: 1,
+        wins: 1,
+        draws: 0,
+        losses: 0
+    }));
+    assert_eq!(find(START, "d2d4"), None);
+    let after_e4 = key("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    assert_eq!(find(after_e4, "c7c5").unwrap().games, 1);
+    assert!(stats.iter().all(|(_, _, move_stats)| move_stats.wins == move_stats.games));
+}
@@ -0,0 +1,71 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::cache::*;
+use std::io::Cursor;
+
+mod common;
+
+fn book() -> PolyglotReader<Cursor<Vec<u8>>> {
+    common::book(&[(1, "e2e4", 1), (2, "e2e4", 2), (3, "e2e4", 3), (4, "e2e4", 4)])
+}
+
+#[test]
+fn test_cache_hits_and_misses() {
+    let mut cached = CachedReader::new(book(), 2);
+    assert_eq!(cached.get_hash(1).unwrap()[0].weight, 1);
+    assert_eq!(cached.get_hash(1).unwrap()[0].weight, 1);
+    assert!(cached.get_hash(5).unwrap().is_empty());
+    assert!(cached.get_hash(5).unwrap().is_empty());
+    assert_eq!((cached.hits(), cached.misses()), (2, 2));
+    assert_eq!(cached.len(), 2);
+
+    cached.clear();
+    assert!(cached.is_empty());
+    assert_eq!((cached.hits(), cached.misses()), (0, 0));
+}
+
+#[test]
+fn test_cache_evicts_least_recently_used() {
+    let mut cached = CachedReader::new(book(), 2);
+    cached.get_hash(1).unwrap();
+    cached.get_hash(2).unwrap();
+    cached.get_hash(1).unwrap();
+    cached.get_hash(3).unwrap();
+    assert_eq!(cached.len(), 2);
+    assert_eq!((cached.hits(), cached.misses()), (1, 3));
+
+    cached.get_hash(1).unwrap();
+    assert_eq!(cached.hits(), 2);
+    cached.get_hash(2).unwrap();
+    assert_eq!(cached.misses(), 4);
+}
+
+#[test]
+fn test_cache_without_capacity() {
+    let mut cached = CachedReader::new(book(), 0);
+    cached.get_hash(1).unwrap();
+    cached.get_hash(1).unwrap();
+    assert!(cached.is_empty());
+    assert_eq!((cached.hits(), cached.misses()), (0, 2));
+}
+
+#[test]
+fn test_cache_prewarm() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let reader = PolyglotReader::new(std::io::BufReader::new(file)).unwrap();
+    let mut cached = CachedReader::new(reader, 1000);
+    let loaded = cached.prewarm(2).unwrap();
+    assert!(loaded > 4);
+    assert_eq!(cached.len(), loaded);
+    assert_eq!(cached.misses(), 0);
+
+    let start: PolyglotKey = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse().unwrap();
+    let after_e4: PolyglotKey = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1".parse().unwrap();
+    assert_eq!(cached.get(&start).unwrap().len(), 3);
+    cached.get(&after_e4).unwrap();
+    assert_eq!((cached.hits(), cached.misses()), (2, 0));
+
+    let mut small = CachedReader::new(cached.into_inner(), 2);
+    assert_eq!(small.prewarm(2).unwrap(), 2);
+    small.get(&start).unwrap();
+    assert_eq!(small.hits(), 1);
+}
@@ -0,0 +1,52 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::chain::*;
+use std::io::Cursor;
+
+mod common;
+use common::book;
+
+fn chain(mode: ChainMode) -> BookChain<Cursor<Vec<u8>>> {
+    let mut chain = BookChain::new(mode);
+    chain.push(book(&[(1, "e2e4", 10)]), 2.0);
+    chain.push(book(&[(1, "d2d4", 8), (1, "e2e4", 4), (2, "c2c4", 3)]), 0.5);
+    chain
+}
+
+fn summary(entries: &[ChainedEntry]) -> Vec<(String, u16, usize)> {
+    entries.iter().map(|chained| (chained.entry.mv.to_string(), chained.entry.weight, chained.book)).collect()
+}
+
+#[test]
+fn test_chain_fallback() {
+    let mut chain = chain(ChainMode::Fallback);
+    assert_eq!(chain.len(), 2);
+    assert_eq!(summary(&chain.get_hash(1).unwrap()), vec![("e2e4".to_string(), 10, 0)]);
+    assert_eq!(summary(&chain.get_hash(2).unwrap()), vec![("c2c4".to_string(), 3, 1)]);
+    assert!(chain.get_hash(3).unwrap().is_empty());
+}
+
+#[test]
+fn test_chain_merge() {
+    let mut chain = chain(ChainMode::Merge);
+    assert_eq!(summary(&chain.get_hash(1).unwrap()), vec![
+        ("e2e4".to_string(), 22, 0),
+        ("d2d4".to_string(), 4, 1)
+    ]);
+    assert_eq!(summary(&chain.get_hash(2).unwrap()), vec![("c2c4".to_string(), 2, 1)]);
+
+    chain.push(book(&[(1, "g1f3", u16::MAX)]), 3.0);
+    let entries = chain.get_hash(1).unwrap();
+    assert_eq!(entries[0].entry.weight, u16::MAX);
+    assert_eq!(entries[0].book, 2);
+}
+
+#[test]
+fn test_chain_get() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let mut chain = BookChain::new(ChainMode::Fallback);
+    chain.push(PolyglotReader::new(std::io::BufReader::new(file)).unwrap(), 1.0);
+    let start: PolyglotKey = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse().unwrap();
+    let expected = chain.book(0).unwrap().get(&start).unwrap();
+    let entries: Vec<_> = chain.get(&start).unwrap().into_iter().map(|chained| chained.entry).collect();
+    assert_eq!(entries, expected);
+}
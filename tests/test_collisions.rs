@@ -0,0 +1,48 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::collisions::*;
+use std::io::Cursor;
+
+mod common;
+use common::book;
+
+const START: u64 = 0x463b96181691fc9c;
+const AFTER_E4: u64 = 0x823c9b50fd114196;
+
+#[test]
+fn test_check_collisions() {
+    let mut reader = book(&[(1, "a2a3", 1), (START, "e2e4", 1), (START, "e2e5", 1), (AFTER_E4, "c7c5", 1), (AFTER_E4, "e1e2", 1)]);
+    let report = check_collisions(&mut reader, &CollisionOptions::default()).unwrap();
+    assert_eq!(report.checked_positions, 2);
+    assert_eq!(report.checked_entries, 4);
+    assert_eq!(report.unchecked_positions, 1);
+    let suspicious: Vec<_> = report.suspicious.iter().map(|suspicious| (suspicious.key, suspicious.entry.mv.to_string())).collect();
+    assert_eq!(suspicious, vec![(START, "e2e5".to_string()), (AFTER_E4, "e1e2".to_string())]);
+    assert_eq!(report.suspicious[1].fen, "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+
+    let mut writer = PolyglotWriter::new(Vec::new());
+    assert_eq!(strip_suspicious(&mut reader, &mut writer, &report).unwrap(), 2);
+    let mut stripped = PolyglotReader::new(Cursor::new(writer.into_inner())).unwrap();
+    assert_eq!(stripped.len(), 3);
+    assert!(check_collisions(&mut stripped, &CollisionOptions::default()).unwrap().is_clean());
+}
+
+#[test]
+fn test_check_collisions_castling() {
+    let mut reader = book(&[(START, "e1h1", 1), (START, "g1f3", 1)]);
+    let report = check_collisions(&mut reader, &CollisionOptions::default()).unwrap();
+    assert_eq!(report.suspicious.len(), 1);
+    assert_eq!(report.suspicious[0].entry.mv.to_string(), "e1h1");
+}
+
+#[test]
+fn test_check_collisions_test_book() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(std::io::BufReader::new(file)).unwrap();
+    let report = check_collisions(&mut reader, &CollisionOptions {
+        max_plies: Some(6),
+        ..CollisionOptions::default()
+    }).unwrap();
+    assert!(report.checked_positions > 0);
+    assert_eq!(report.checked_positions + report.unchecked_positions, reader.positions().unwrap().count());
+    assert!(report.is_clean());
+}
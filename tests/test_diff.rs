@@ -0,0 +1,57 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::diff::*;
+
+mod common;
+use common::book;
+
+const START: u64 = 0x463b96181691fc9c;
+const AFTER_E4: u64 = 0x823c9b50fd114196;
+const E2E4: &str = "e2e4";
+const D2D4: &str = "d2d4";
+const C7C5: &str = "c7c5";
+
+#[test]
+fn test_diff_identical() {
+    use std::fs::File;
+
+    let mut old = PolyglotReader::new(File::open("test-data/test_book.bin").unwrap()).unwrap();
+    let mut new = PolyglotReader::new(File::open("test-data/test_book.bin").unwrap()).unwrap();
+    let diff = diff(&mut old, &mut new, &DiffOptions::default()).unwrap();
+    assert!(diff.is_empty());
+}
+
+#[test]
+fn test_diff() {
+    let mut old = book(&[(1, E2E4, 10), (START, E2E4, 10), (START, D2D4, 5), (START + 1, E2E4, 1)]);
+    let mut new = book(&[(START, D2D4, 8), (START, C7C5, 1), (START + 1, E2E4, 2), (AFTER_E4, C7C5, 3)]);
+    let diff = diff(&mut old, &mut new, &DiffOptions { weight_threshold: 2 }).unwrap();
+
+    assert_eq!(diff.removed_positions.len(), 1);
+    assert_eq!(diff.removed_positions[0].0, 1);
+    assert_eq!(diff.added_positions.len(), 1);
+    assert_eq!(diff.added_positions[0].0, AFTER_E4);
+    assert_eq!(diff.removed_moves.len(), 1);
+    assert_eq!(diff.removed_moves[0].1.mv, E2E4.parse::<Move>().unwrap());
+    assert_eq!(diff.added_moves.len(), 1);
+    assert_eq!(diff.added_moves[0].1.mv, C7C5.parse::<Move>().unwrap());
+    assert_eq!(diff.weight_changes, vec![WeightChange {
+        key: START,
+        mv: D2D4.parse::<Move>().unwrap(),
+        old: 5,
+        new: 8
+    }]);
+    assert_eq!(diff.keys(), vec![1, START, AFTER_E4]);
+}
+
+#[test]
+fn test_diff_resolve_lines() {
+    let mut old = book(&[(START, E2E4, 10)]);
+    let mut new = book(&[(START, E2E4, 10), (AFTER_E4, C7C5, 3)]);
+    let diff = diff(&mut old, &mut new, &DiffOptions::default()).unwrap();
+    let lines = diff.resolve_lines(&mut old, &mut new).unwrap();
+
+    assert_eq!(lines.len(), 1);
+    let line = &lines[&AFTER_E4];
+    assert_eq!(line.moves, vec![E2E4.parse::<Move>().unwrap()]);
+    assert_eq!(line.fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+}
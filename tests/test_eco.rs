@@ -0,0 +1,42 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::eco::*;
+
+fn hash(fen: &str) -> u64 {
+    fen.parse::<PolyglotKey>().unwrap().polyglot_hash()
+}
+
+#[test]
+fn test_classify() {
+    let sicilian = classify(hash("rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2")).unwrap();
+    assert_eq!(sicilian.eco, "B20");
+    assert_eq!(sicilian.name, "Sicilian Defense");
+    assert_eq!(sicilian.moves, "e4 c5");
+    assert_eq!(sicilian.to_string(), "B20 Sicilian Defense");
+
+    let najdorf = classify(hash("rnbqkb1r/1p2pppp/p2p1n2/8/3NP3/2N5/PPP2PPP/R1BQKB1R w KQkq - 0 6")).unwrap();
+    assert_eq!(najdorf.eco, "B90");
+
+    assert_eq!(classify(hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")), None);
+    assert_eq!(classify(0), None);
+}
+
+#[test]
+fn test_classify_transposition() {
+    // 1. d4 d5 2. c4 c6 3. Nf3 Nf6 4. Nc3 e6 reaches the table's Semi-Slav line by another move order.
+    let semi_slav = classify(hash("rnbqkb1r/pp3ppp/2p1pn2/3p4/2PP4/2N2N2/PP2PPPP/R1BQKB1R w KQkq - 0 5")).unwrap();
+    assert_eq!(semi_slav.eco, "D43");
+    assert_eq!(semi_slav.moves, "d4 d5 c4 e6 Nc3 Nf6 Nf3 c6");
+}
+
+#[test]
+fn test_classify_line() {
+    let line = [
+        hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+        hash("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"),
+        hash("rnbqkbnr/pppp1ppp/4p3/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2"),
+        hash("rnbqkbnr/pppp1ppp/4p3/8/4P3/7P/PPPP1PP1/RNBQKBNR b KQkq - 0 2")
+    ];
+    assert_eq!(classify_line(&line).unwrap().name, "French Defense");
+    assert_eq!(classify_line(&line[..2]).unwrap().name, "King's Pawn Game");
+    assert_eq!(classify_line(&line[..1]), None);
+}
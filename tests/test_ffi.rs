@@ -0,0 +1,46 @@
+use chess_polyglot_reader::ffi::*;
+use std::ffi::CString;
+
+#[test]
+fn test_ffi() {
+    unsafe {
+        let path = CString::new("test-data/test_book.bin").unwrap();
+        let book = polyglot_book_open(path.as_ptr());
+        assert!(!book.is_null());
+        assert_eq!(polyglot_book_len(book), 92954);
+
+        let fen = CString::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+        let mut hash = 0;
+        assert!(polyglot_hash_fen(fen.as_ptr(), &mut hash));
+        assert_eq!(hash, 0x463b96181691fc9c);
+
+        let probe = polyglot_book_probe_fen(book, fen.as_ptr());
+        assert!(!probe.is_null());
+        assert_eq!(polyglot_probe_len(probe), 3);
+        let mut entry = PolyglotRawEntry { mv: 0, weight: 0, learn: 0 };
+        assert!(polyglot_probe_get(probe, 0, &mut entry));
+        assert_eq!(entry, PolyglotRawEntry { mv: 0x031c, weight: 1, learn: 486544384 });
+        assert!(!polyglot_probe_get(probe, 3, &mut entry));
+        polyglot_probe_free(probe);
+
+        let probe = polyglot_book_probe_hash(book, hash);
+        assert_eq!(polyglot_probe_len(probe), 3);
+        polyglot_probe_free(probe);
+
+        let probe = polyglot_book_probe_hash(book, 0);
+        assert!(!probe.is_null());
+        assert_eq!(polyglot_probe_len(probe), 0);
+        polyglot_probe_free(probe);
+
+        let invalid = CString::new("not a fen").unwrap();
+        assert!(!polyglot_hash_fen(invalid.as_ptr(), &mut hash));
+        assert!(polyglot_book_probe_fen(book, invalid.as_ptr()).is_null());
+        polyglot_book_free(book);
+
+        let missing = CString::new("test-data/missing.bin").unwrap();
+        assert!(polyglot_book_open(missing.as_ptr()).is_null());
+        assert!(polyglot_book_open(std::ptr::null()).is_null());
+        assert_eq!(polyglot_book_len(std::ptr::null()), 0);
+        polyglot_book_free(std::ptr::null_mut());
+    }
+}
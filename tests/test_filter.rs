@@ -0,0 +1,81 @@
+use chess_polyglot_reader::Side;
+use chess_polyglot_reader::filter::*;
+use chess_polyglot_reader::pgn::*;
+
+#[test]
+fn test_time_controls() {
+    let tests = [
+        ("60", Some(TimeControl::Bullet)),
+        ("120+1", Some(TimeControl::Bullet)),
+        ("180+2", Some(TimeControl::Blitz)),
+        ("300", Some(TimeControl::Blitz)),
+        ("600+5", Some(TimeControl::Rapid)),
+        ("5400+30", Some(TimeControl::Classical)),
+        ("40/7200:3600", Some(TimeControl::Classical)),
+        ("*90", Some(TimeControl::Bullet)),
+        ("-", Some(TimeControl::Correspondence)),
+        ("?", None),
+        ("3 min", None),
+        ("1+999999999999999999", None)
+    ];
+    for (tag, expected) in tests {
+        assert_eq!(TimeControl::from_pgn(tag), expected, "Categorising '{}'", tag);
+    }
+}
+
+#[test]
+fn test_dates() {
+    let date = |year, month, day| PgnDate { year, month, day };
+    assert_eq!("2021.03.04".parse::<PgnDate>().unwrap(), date(2021, 3, 4));
+    assert_eq!("2021-03-04".parse::<PgnDate>().unwrap(), date(2021, 3, 4));
+    assert_eq!("2021.??.??".parse::<PgnDate>().unwrap(), date(2021, 0, 0));
+    assert_eq!("????.??.??".parse::<PgnDate>(), Err(ParseDateError));
+    assert!("2021.13.01".parse::<PgnDate>().is_err());
+    assert!(date(2021, 0, 0) < date(2021, 1, 1));
+}
+
+#[test]
+fn test_result_filter() {
+    let white_wins = Some(GameResult::WhiteWins);
+    let draw = Some(GameResult::Draw);
+    assert!(ResultFilter::All.includes(None, Side::White));
+    assert!(!ResultFilter::Finished.includes(None, Side::White));
+    assert!(ResultFilter::Finished.includes(draw, Side::Black));
+    assert!(!ResultFilter::Decisive.includes(draw, Side::White));
+    assert!(ResultFilter::Decisive.includes(white_wins, Side::Black));
+    assert!(ResultFilter::Winner.includes(white_wins, Side::White));
+    assert!(!ResultFilter::Winner.includes(white_wins, Side::Black));
+    assert!(!ResultFilter::Winner.includes(draw, Side::White));
+    assert!(ResultFilter::NotLoser.includes(draw, Side::Black));
+    assert!(!ResultFilter::NotLoser.includes(white_wins, Side::Black));
+}
+
+#[test]
+fn test_accepts() {
+    let game = PgnGame {
+        tags: vec![
+            ("WhiteElo".to_string(), "2400".to_string()),
+            ("BlackElo".to_string(), "2300".to_string()),
+            ("TimeControl".to_string(), "180+2".to_string()),
+            ("Date".to_string(), "2021.03.04".to_string())
+        ],
+        moves: vec!["e4".to_string(), "e5".to_string()],
+        result: Some(GameResult::Draw)
+    };
+    assert!(GameFilter::default().accepts(&game));
+    let accepts = |filter: GameFilter| filter.accepts(&game);
+    assert!(accepts(GameFilter { min_elo: Some(2300), ..GameFilter::default() }));
+    assert!(!accepts(GameFilter { min_elo: Some(2301), ..GameFilter::default() }));
+    assert!(accepts(GameFilter { time_controls: vec![TimeControl::Blitz], ..GameFilter::default() }));
+    assert!(!accepts(GameFilter { time_controls: vec![TimeControl::Rapid], ..GameFilter::default() }));
+    assert!(accepts(GameFilter { result: ResultFilter::NotLoser, ..GameFilter::default() }));
+    assert!(!accepts(GameFilter { result: ResultFilter::Winner, ..GameFilter::default() }));
+    assert!(accepts(GameFilter { min_date: "2021.03.04".parse().ok(), ..GameFilter::default() }));
+    assert!(!accepts(GameFilter { max_date: "2021.03.03".parse().ok(), ..GameFilter::default() }));
+    assert!(accepts(GameFilter { min_plies: 2, ..GameFilter::default() }));
+    assert!(!accepts(GameFilter { min_plies: 3, ..GameFilter::default() }));
+
+    let untagged = PgnGame::default();
+    assert!(!GameFilter { min_elo: Some(0), ..GameFilter::default() }.accepts(&untagged));
+    assert!(!GameFilter { min_date: "1900.01.01".parse().ok(), ..GameFilter::default() }.accepts(&untagged));
+}
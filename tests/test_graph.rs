@@ -0,0 +1,118 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::board::Board;
+use chess_polyglot_reader::graph::*;
+use chess_polyglot_reader::keys::KeyTable;
+use std::io::{BufReader, Cursor};
+
+mod common;
+
+fn key(fen: &str) -> u64 {
+    fen.parse::<PolyglotKey>().unwrap().polyglot_hash()
+}
+
+fn shuffle_book() -> PolyglotReader<Cursor<Vec<u8>>> {
+    // 1. Nf3 Nf6 2. Ng1 Ng8 repeats the start position.
+    common::book(&[
+        (key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"), "g1f3", 10),
+        (key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"), "e2e4", 20),
+        (key("rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1"), "g8f6", 5),
+        (key("rnbqkb1r/pppppppp/5n2/8/8/5N2/PPPPPPPP/RNBQKB1R w KQkq - 2 2"), "f3g1", 1),
+        (key("rnbqkb1r/pppppppp/5n2/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 3 2"), "f6g8", 1)
+    ])
+}
+
+#[test]
+fn test_graph_cycles() {
+    let mut reader = shuffle_book();
+    let graph = BookGraph::build(&mut reader, &[Board::default()], None).unwrap();
+    let start = key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    assert_eq!(graph.roots, vec![start]);
+    assert_eq!(graph.nodes.len(), 5);
+    assert_eq!(graph.edges.len(), 5);
+    assert_eq!(graph.out_degree(start), 2);
+    assert_eq!(graph.in_degree(start), 1);
+    assert_eq!(graph.nodes[&key("rnbqkb1r/pppppppp/5n2/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 3 2")].ply, 3);
+    assert!(graph.transpositions().is_empty());
+
+    let cycles = graph.cycles();
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles[0].len(), 4);
+    assert!(cycles[0].contains(&start));
+    assert!(!cycles[0].contains(&key("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1")));
+
+    let limited = BookGraph::build(&mut reader, &[Board::default()], Some(2)).unwrap();
+    assert_eq!(limited.nodes.len(), 4);
+    assert!(limited.cycles().is_empty());
+}
+
+#[test]
+fn test_graph_dot() {
+    let mut reader = shuffle_book();
+    let graph = BookGraph::build(&mut reader, &[Board::default()], None).unwrap();
+    let start = key("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    let mut dot = Vec::new();
+    graph.write_dot(&mut dot, &DotOptions {
+        root: start,
+        max_depth: 1,
+        eco_labels: false
+    }).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.starts_with("digraph book {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert_eq!(dot.lines().filter(|line| line.contains("->")).count(), 2);
+    assert!(dot.contains(&format!("\"{:016x}\" -> \"{:016x}\" [label=\"e4 (20)\"];", start, key("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"))));
+    assert!(dot.contains("[label=\"Nf3 (10)\"]"));
+}
+
+#[test]
+fn test_graph_transpositions() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(BufReader::new(file)).unwrap();
+    let graph = BookGraph::build(&mut reader, &[Board::default()], Some(4)).unwrap();
+    let lines = walk::reachable_lines(&mut reader, &[Board::default()], Some(4)).unwrap();
+    assert_eq!(graph.nodes.len(), lines.len());
+
+    let transpositions = graph.transpositions();
+    assert!(!transpositions.is_empty());
+    for &(key, count) in &transpositions {
+        assert!(count > 1);
+        assert_eq!(graph.in_degree(key), count);
+        assert!(graph.nodes[&key].ply >= 3);
+    }
+    let in_degrees: usize = graph.nodes.keys().map(|&key| graph.in_degree(key)).sum();
+    assert_eq!(in_degrees, graph.edges.len());
+}
+
+#[test]
+fn test_graph_dot_eco_labels() {
+    let mut reader = shuffle_book();
+    let graph = BookGraph::build(&mut reader, &[Board::default()], None).unwrap();
+    let nf3 = key("rnbqkbnr/pppppppp/8/8/8/5N2/PPPPPPPP/RNBQKB1R b KQkq - 1 1");
+    let mut dot = Vec::new();
+    graph.write_dot(&mut dot, &DotOptions {
+        root: nf3,
+        max_depth: 1,
+        eco_labels: true
+    }).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.contains(&format!("\"{:016x}\" [label=\"{:016x}\\nA04 Zukertort Opening\"", nf3, nf3)));
+    assert!(dot.contains("\\nA05 Zukertort Opening\""));
+}
+
+#[test]
+fn test_graph_dot_eco_labels_custom_table() {
+    let table = KeyTable::from_seed(42);
+    let start = Board::default();
+    let book = common::book_bytes(&[(start.polyglot_hash_with(&table), "g1f3", 1)]);
+    let mut reader = PolyglotReader::with_table(Cursor::new(book), table.clone()).unwrap();
+    let graph = BookGraph::build(&mut reader, &[start], None).unwrap();
+    let nf3 = start.play("g1f3".parse().unwrap()).unwrap().polyglot_hash_with(&table);
+    let mut dot = Vec::new();
+    graph.write_dot(&mut dot, &DotOptions {
+        root: start.polyglot_hash_with(&table),
+        max_depth: 1,
+        eco_labels: true
+    }).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert!(dot.contains(&format!("\"{:016x}\" [label=\"{:016x}\\nA04 Zukertort Opening\"", nf3, nf3)));
+}
@@ -0,0 +1,107 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::interchange::*;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+fn sorted_records(bytes: &[u8]) -> Vec<(u64, u16, u16, u32)> {
+    let mut reader = PolyglotReader::new(Cursor::new(bytes)).unwrap();
+    let mut records: Vec<_> = reader.entries().unwrap()
+        .map(|entry| {
+            let (key, entry) = entry.unwrap();
+            (key, entry.mv.to_u16(), entry.weight, entry.learn)
+        })
+        .collect();
+    records.sort_unstable();
+    records
+}
+
+#[test]
+fn test_csv_round_trip() {
+    let bytes = std::fs::read("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(Cursor::new(&bytes)).unwrap();
+    let mut csv = Vec::new();
+    let rows = export_csv(&mut reader, &mut csv, &HashMap::new()).unwrap();
+    assert_eq!(rows, reader.len());
+    assert!(csv.starts_with(b"key,move,weight,learn,fen\n0000968b7fcb1868,a8b8,5,2919175168,\n"));
+
+    let records = read_csv(Cursor::new(csv)).unwrap();
+    assert_eq!(records.len(), rows);
+    let mut writer = PolyglotWriter::new(Vec::new());
+    assert_eq!(write_book(&records, &mut writer).unwrap(), rows);
+    assert_eq!(sorted_records(&writer.into_inner()), sorted_records(&bytes));
+}
+
+#[test]
+fn test_csv_edits() {
+    let csv = "\
+Weight,Move,Key,Fen
+5,e2e4,463b96181691fc9c,\"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1\"
+\"10\", d2d4 ,0x463b96181691fc9c,
+1,e7e5,823c9b50fd114196,
+
+";
+    let records = read_csv(Cursor::new(csv)).unwrap();
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[0].fen.as_deref(), Some("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"));
+    assert_eq!(records[1].fen, None);
+    assert_eq!(records[1].learn, 0);
+
+    let mut writer = PolyglotWriter::new(Vec::new());
+    write_book(&records, &mut writer).unwrap();
+    let mut reader = PolyglotReader::new(Cursor::new(writer.into_inner())).unwrap();
+    let moves: Vec<_> = reader.get_hash(0x463b96181691fc9c).unwrap().iter().map(|entry| entry.mv.to_string()).collect();
+    assert_eq!(moves, vec!["d2d4", "e2e4"]);
+
+    let duplicate = vec![records[0].clone(), records[0].clone()];
+    assert!(write_book(&duplicate, &mut PolyglotWriter::new(Vec::new())).is_err());
+}
+
+#[test]
+fn test_csv_errors() {
+    for csv in [
+        "move,weight\ne2e4,1",
+        "key,move,weight\nxyz,e2e4,1",
+        "key,move,weight\n1,e2e9,1",
+        "key,move,weight\n1,e2e4,65536",
+        "key,move,weight,learn\n1,e2e4,1,-1"
+    ] {
+        assert!(read_csv(Cursor::new(csv)).is_err(), "Reading '{}'", csv);
+    }
+    let error = read_csv(Cursor::new("key,move,weight\n1,e2e4,1\n2,e2e4,x")).unwrap_err();
+    assert_eq!(error.to_string(), "Line 3: invalid weight");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json_lines_round_trip() {
+    let bytes = std::fs::read("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(Cursor::new(&bytes)).unwrap();
+    let mut json = Vec::new();
+    let mut fens = HashMap::new();
+    fens.insert(0x463b96181691fc9c, "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string());
+    export_json_lines(&mut reader, &mut json, &fens).unwrap();
+    let first = String::from_utf8(json.split(|&b| b == b'\n').next().unwrap().to_vec()).unwrap();
+    assert_eq!(first, r#"{"key":"0000968b7fcb1868","move":"a8b8","weight":5,"learn":2919175168}"#);
+
+    let records = read_json_lines(Cursor::new(json)).unwrap();
+    assert_eq!(records.iter().filter(|record| record.fen.is_some()).count(), 3);
+    let mut writer = PolyglotWriter::new(Vec::new());
+    write_book(&records, &mut writer).unwrap();
+    assert_eq!(sorted_records(&writer.into_inner()), sorted_records(&bytes));
+}
+
+#[test]
+fn test_reachable_fens() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(std::io::BufReader::new(file)).unwrap();
+    let fens = reachable_fens(&mut reader).unwrap();
+    let mut csv = Vec::new();
+    export_csv(&mut reader, &mut csv, &fens).unwrap();
+    let records = read_csv(Cursor::new(csv)).unwrap();
+    let start: Vec<_> = records.iter().filter(|record| record.key == 0x463b96181691fc9c).collect();
+    assert_eq!(start.len(), 3);
+    for record in start {
+        let key: PolyglotKey = record.fen.as_deref().unwrap().parse().unwrap();
+        assert_eq!(key.polyglot_hash(), record.key);
+    }
+}
@@ -0,0 +1,66 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::board::Board;
+use chess_polyglot_reader::build::*;
+use chess_polyglot_reader::keys::KeyTable;
+use chess_polyglot_reader::replay::*;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+const START: u64 = 0x463b96181691fc9c;
+
+#[test]
+fn test_standard_table() {
+    assert_eq!(KeyTable::default(), KeyTable::STANDARD);
+    let key = Board::default().key();
+    assert_eq!(key.polyglot_hash_with(&KeyTable::STANDARD), START);
+    assert_eq!(key.polyglot_hash(), START);
+
+    let bytes = KeyTable::STANDARD.to_bytes();
+    assert_eq!(bytes.len(), KeyTable::LEN * 8);
+    assert_eq!(bytes[..8], 0x9D39247E33776D41u64.to_be_bytes());
+    assert_eq!(bytes[bytes.len() - 8..], 0xF8D626AAAF278509u64.to_be_bytes());
+    assert_eq!(KeyTable::from_bytes(&bytes), Some(KeyTable::STANDARD));
+    assert_eq!(KeyTable::from_bytes(&bytes[8..]), None);
+}
+
+#[test]
+fn test_custom_table() {
+    let table = KeyTable::from_seed(42);
+    assert_eq!(table, KeyTable::from_seed(42));
+    assert_ne!(table, KeyTable::from_seed(43));
+    assert_eq!(KeyTable::from_bytes(&table.to_bytes()).as_ref(), Some(&table));
+
+    let board: Board = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+    let custom = board.polyglot_hash_with(&table);
+    assert_ne!(custom, board.polyglot_hash());
+    let mut key = board.key();
+    key.white_castle.king_side = false;
+    assert_eq!(key.polyglot_hash_with(&table), custom ^ table.castle[0]);
+}
+
+#[test]
+fn test_custom_table_books() {
+    let table = KeyTable::from_seed(42);
+    let paths = [PathBuf::from("test-data/games_a.pgn"), PathBuf::from("test-data/games_b.pgn")];
+    let mut writer = PolyglotWriter::new(Vec::new());
+    build_book(&paths, &mut writer, &BuildOptions {
+        table: table.clone(),
+        ..BuildOptions::default()
+    }).unwrap();
+    let book = writer.into_inner();
+
+    let start = Board::default().key();
+    let mut standard = PolyglotReader::new(Cursor::new(book.clone())).unwrap();
+    assert!(standard.get(&start).unwrap().is_empty());
+    let mut reader = PolyglotReader::with_table(Cursor::new(book), table.clone()).unwrap();
+    assert_eq!(reader.table(), &table);
+    let moves: Vec<_> = reader.get(&start).unwrap().iter().map(|entry| entry.mv.to_string()).collect();
+    assert_eq!(moves, vec!["e2e4", "d2d4"]);
+
+    let replayed = replay(&mut reader, None, ["e2e4", "c7c5", "g1f3"], &ReplayOptions::default()).unwrap();
+    assert_eq!(replayed[0].hash, start.polyglot_hash_with(&table));
+    assert!(replayed[..3].iter().all(|position| position.book_move));
+    let lines = walk::reachable_lines(&mut reader, &[Board::default()], None).unwrap();
+    assert!(lines.contains_key(&start.polyglot_hash_with(&table)));
+    assert!(!lines.contains_key(&START));
+}
@@ -0,0 +1,17 @@
+use chess_polyglot_reader::*;
+use std::io::Cursor;
+
+mod common;
+
+#[test]
+fn test_reader_bounds() {
+    let mut reader = common::book(&[(1, "e2e4", 1), (2, "e2e4", 2), (2, "e2e4", 2), (3, "e2e4", 3)]);
+    assert_eq!(reader.get_hash(1).unwrap().len(), 1);
+    assert_eq!(reader.get_hash(2).unwrap().len(), 2);
+    assert_eq!(reader.get_hash(3).unwrap().len(), 1);
+    assert!(reader.get_hash(0).unwrap().is_empty());
+    assert!(reader.get_hash(4).unwrap().is_empty());
+
+    let mut empty = PolyglotReader::new(Cursor::new(Vec::new())).unwrap();
+    assert!(empty.get_hash(1).unwrap().is_empty());
+}
@@ -0,0 +1,86 @@
+use chess_polyglot_reader::*;
+
+#[test]
+fn test_squares() {
+    for index in 0..64 {
+        let square = Square::from_index(index).unwrap();
+        assert_eq!(square.index(), index);
+        assert_eq!(Square::new(square.rank(), square.file()), Some(square));
+        assert_eq!(square.to_string().parse::<Square>(), Ok(square));
+        assert_eq!(square.mirrored().mirrored(), square);
+    }
+    assert_eq!(Square::from_index(64), None);
+    assert_eq!(Square::new(8, 0), None);
+    assert_eq!(Square::new(0, 8), None);
+    assert_eq!(Square::new(9, 12), None);
+
+    let e4: Square = "e4".parse().unwrap();
+    assert_eq!((e4.rank(), e4.file()), (3, 4));
+    assert_eq!(e4.mirrored().to_string(), "e5");
+    for invalid in ["", "e", "e9", "i4", "E4", "e44", "4e"] {
+        assert_eq!(invalid.parse::<Square>(), Err(ParseSquareError), "Parsing '{}'", invalid);
+    }
+}
+
+#[test]
+fn test_move_round_trip() {
+    let mut valid = 0;
+    for mv in 0..=u16::MAX {
+        match Move::try_from_u16(mv) {
+            Some(decoded) => {
+                valid += 1;
+                assert_eq!(decoded.to_u16(), mv, "Testing round trip of {:#06x}", mv);
+                assert_eq!(Move::from_u16(mv), decoded);
+                assert_eq!(Move::new(decoded.source(), decoded.dest(), decoded.promotion()), Some(decoded));
+            }
+            None => assert!(mv >> 12 > 4, "Testing rejection of {:#06x}", mv)
+        }
+    }
+    assert_eq!(valid, 5 * 64 * 64);
+}
+
+#[test]
+fn test_move_fields() {
+    let mv = Move::from_u16(0x031c);
+    assert_eq!(mv.source(), "e2".parse().unwrap());
+    assert_eq!(mv.dest(), "e4".parse().unwrap());
+    assert_eq!(mv.promotion(), None);
+
+    let promotion = Move::from_u16(0x4dff);
+    assert_eq!(promotion.source(), "h7".parse().unwrap());
+    assert_eq!(promotion.dest(), "h8".parse().unwrap());
+    assert_eq!(promotion.promotion(), Some(PieceType::Queen));
+
+    let a7: Square = "a7".parse().unwrap();
+    let a8: Square = "a8".parse().unwrap();
+    assert!(Move::new(a7, a8, Some(PieceType::Knight)).is_some());
+    assert!(Move::new(a7, a8, Some(PieceType::King)).is_none());
+    assert!(Move::new(a7, a8, Some(PieceType::Pawn)).is_none());
+}
+
+#[test]
+fn test_move_from_u16_top_bit() {
+    assert_eq!(Move::from_u16(0x831c), Move::from_u16(0x031c));
+    assert_eq!(Move::try_from_u16(0x831c), None);
+}
+
+#[test]
+fn test_reader_invalid_move() {
+    use std::io::{Cursor, ErrorKind};
+
+    let mut book = Vec::new();
+    for (key, mv) in [(1u64, 0x031cu16), (2, 0x7000 | 0x031c), (3, 0x8000 | 0x031c)] {
+        book.extend_from_slice(&key.to_be_bytes());
+        book.extend_from_slice(&mv.to_be_bytes());
+        book.extend_from_slice(&[0; 6]);
+    }
+    let mut reader = PolyglotReader::new(Cursor::new(book)).unwrap();
+    assert_eq!(reader.get_hash(1).unwrap().len(), 1);
+    assert_eq!(reader.get_hash(2).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert_eq!(reader.get_hash(3).unwrap_err().kind(), ErrorKind::InvalidData);
+
+    let entries: Vec<_> = reader.entries().unwrap().collect();
+    assert!(entries[0].is_ok());
+    assert!(entries[1..].iter().all(|entry| entry.as_ref().unwrap_err().kind() == ErrorKind::InvalidData));
+    assert!(reader.positions().unwrap().any(|position| position.is_err()));
+}
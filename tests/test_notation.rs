@@ -0,0 +1,94 @@
+use chess_polyglot_reader::*;
+
+const KEY_TESTS: &[(&str, u64)] = &[
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", 0x463b96181691fc9c),
+    ("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1", 0x823c9b50fd114196),
+    ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", 0x0756b94461c50fb0),
+    ("rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR b KQkq - 0 2", 0x662fafb965db29d4),
+    ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", 0x22a48b5a8e47ff78),
+    ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR b kq - 0 3", 0x652a607ca3f242c1),
+    ("rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4", 0x00fdd303c946bdd9),
+    ("rnbqkbnr/p1pppppp/8/8/PpP4P/8/1P1PPPP1/RNBQKBNR b KQkq c3 0 3", 0x3c8123ea7b067637),
+    ("rnbqkbnr/p1pppppp/8/8/P6P/R1p5/1P1PPPP1/1NBQKBNR b Kkq - 0 4", 0x5c3f9b829b279560)
+];
+
+const SAN_TESTS: &[(&str, &str, &str)] = &[
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e2e4", "e4"),
+    ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "g1f3", "Nf3"),
+    ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", "e4d5", "exd5"),
+    ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "e5f6", "exf6"),
+    ("3q4/4P3/8/8/8/8/8/k6K w - - 0 1", "e7e8q", "e8=Q"),
+    ("3q4/4P3/8/8/8/8/8/k6K w - - 0 1", "e7d8n", "exd8=N"),
+    ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1h1", "O-O"),
+    ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1", "O-O"),
+    ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "e8a8", "O-O-O"),
+    ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "a1a8", "Rxa8+"),
+    ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1f1", "Kf1"),
+    ("rnbqkbnr/pppppppp/8/8/8/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1", "b1d2", "Nbd2"),
+    ("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1a3", "R1a3"),
+    ("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "a1b1", "Rb1"),
+    ("4k3/8/8/8/7Q/8/8/K6Q w - - 0 1", "h4e1", "Q4e1+"),
+    ("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", "h4e1", "Qh4e1"),
+    ("4k3/8/8/8/8/8/2R5/R1R1K3 w - - 0 1", "c1b1", "Rcb1"),
+    ("4k3/8/8/8/4r3/8/2N1N3/4K3 w - - 0 1", "c2d4", "Nd4"),
+    ("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", "a1a8", "Ra8+"),
+    ("6k1/5ppp/8/8/8/8/8/R3K3 w - - 0 1", "a1a8", "Ra8#"),
+    ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1c1", "O-O-O")
+];
+
+#[test]
+fn test_fen_keys() {
+    for (i, &(fen, expected)) in KEY_TESTS.iter().enumerate() {
+        let key: PolyglotKey = fen.parse().unwrap();
+        assert_eq!(key.polyglot_hash(), expected, "Testing hash for '{}' (Test {})", fen, i + 1);
+    }
+    for invalid in ["", "8/8/8/8/8/8/8/8 w", "9/8/8/8/8/8/8/8 w - -", "8/8/8/8/8/8/8/8 x - -", "8/8/8/8/8/8/8/8 w X -"] {
+        assert!(invalid.parse::<PolyglotKey>().is_err(), "Parsing '{}'", invalid);
+    }
+}
+
+#[test]
+fn test_uci() {
+    for uci in ["e2e4", "a1h8", "e7e8q", "b2a1n", "h7h8r", "c2c1b"] {
+        let mv: Move = uci.parse().unwrap();
+        assert_eq!(mv.to_string(), uci);
+    }
+    for invalid in ["", "e2", "e2e", "e2e9", "e7e8k", "e7e8Q", "e7e8qq", "é2e4"] {
+        assert_eq!(invalid.parse::<Move>(), Err(ParseMoveError), "Parsing '{}'", invalid);
+    }
+    assert_eq!(Move::from_u16(0x031c).to_string(), "e2e4");
+}
+
+#[test]
+fn test_castling_normalisation() {
+    let position: PolyglotKey = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+    let castles = [("e1h1", "e1g1"), ("e1a1", "e1c1")];
+    for &(polyglot, standard) in &castles {
+        let polyglot: Move = polyglot.parse().unwrap();
+        let standard: Move = standard.parse().unwrap();
+        assert_eq!(polyglot.to_standard_castling(&position), standard);
+        assert_eq!(standard.from_standard_castling(&position), polyglot);
+        assert_eq!(polyglot.from_standard_castling(&position), polyglot);
+        assert_eq!(standard.to_standard_castling(&position), standard);
+    }
+    let rook_move: Move = "a1d1".parse().unwrap();
+    assert_eq!(rook_move.to_standard_castling(&position), rook_move);
+    assert_eq!(rook_move.from_standard_castling(&position), rook_move);
+
+    let queen: PolyglotKey = "4k3/8/8/8/8/8/8/4Q2R w - - 0 1".parse().unwrap();
+    let queen_move: Move = "e1h1".parse().unwrap();
+    assert_eq!(queen_move.to_standard_castling(&queen), queen_move);
+}
+
+#[test]
+fn test_san() {
+    for (i, &(fen, uci, expected)) in SAN_TESTS.iter().enumerate() {
+        let position: PolyglotKey = fen.parse().unwrap();
+        let mv: Move = uci.parse().unwrap();
+        assert_eq!(mv.to_san(&position).as_deref(), Some(expected), "Testing SAN of {} in '{}' (Test {})", uci, fen, i + 1);
+    }
+    let start: PolyglotKey = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse().unwrap();
+    assert_eq!("e7e5".parse::<Move>().unwrap().to_san(&start), None);
+    assert_eq!("e3e4".parse::<Move>().unwrap().to_san(&start), None);
+    assert_eq!("e2e5".parse::<Move>().unwrap().to_san(&start), None);
+}
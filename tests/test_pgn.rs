@@ -0,0 +1,66 @@
+use chess_polyglot_reader::board::Board;
+use chess_polyglot_reader::pgn::*;
+use std::io::{BufReader, Cursor};
+
+fn moves(text: &str) -> Vec<String> {
+    text.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn test_read_games() {
+    let file = std::fs::File::open("test-data/games_a.pgn").unwrap();
+    let games: Vec<_> = PgnReader::new(BufReader::new(file)).map(Result::unwrap).collect();
+    assert_eq!(games.len(), 3);
+
+    assert_eq!(games[0].tag("White"), Some("Alpha"));
+    assert_eq!(games[0].tag("WhiteElo"), Some("2400"));
+    assert_eq!(games[0].tag("Missing"), None);
+    assert_eq!(games[0].moves, moves("e4 c5 Nf3 d6 d4 cxd4 Nxd4 Nf6 Nc3 a6 Be3 e5"));
+    assert_eq!(games[0].result, Some(GameResult::WhiteWins));
+
+    assert_eq!(games[1].moves, moves("e4 e5 Nf3 Nc6 Bb5 a6 Ba4 Nf6 O-O Be7 Re1 b5"));
+    assert_eq!(games[1].result, Some(GameResult::Draw));
+    assert_eq!(games[2].moves, moves("d4 Nf6 c4 e6 Nc3 Bb4 Qc2 O-O"));
+    assert_eq!(games[2].result, Some(GameResult::BlackWins));
+}
+
+#[test]
+fn test_read_games_without_tags() {
+    let pgn = "1. e4 {a comment\nover two lines} e5 2. Nf3 (2. f4 exf4 (2... d5)) Nc6 *\n\n1. d4 d5 0-1\n[Event \"Tagged\"]\n[Result \"1-0\"]\n[Annotator \"A \\\"quoted\\\" name\"]\n\n1. c4\n";
+    let games: Vec<_> = PgnReader::new(Cursor::new(pgn)).map(Result::unwrap).collect();
+    assert_eq!(games.len(), 3);
+    assert_eq!(games[0].moves, moves("e4 e5 Nf3 Nc6"));
+    assert_eq!(games[0].result, None);
+    assert_eq!(games[1].moves, moves("d4 d5"));
+    assert_eq!(games[2].tag("Annotator"), Some("A \"quoted\" name"));
+    assert_eq!(games[2].moves, moves("c4"));
+    assert_eq!(games[2].result, Some(GameResult::WhiteWins));
+}
+
+#[test]
+fn test_parse_san() {
+    let tests = [
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e4", Some("e2e4")),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "Nf3!?", Some("g1f3")),
+        ("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "e5", None),
+        ("rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2", "exd5", Some("e4d5")),
+        ("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3", "exf6", Some("e5f6")),
+        ("3q4/4P3/8/8/8/8/8/k6K w - - 0 1", "e8=Q+", Some("e7e8q")),
+        ("3q4/4P3/8/8/8/8/8/k6K w - - 0 1", "exd8N", Some("e7d8n")),
+        ("3q4/4P3/8/8/8/8/8/k6K w - - 0 1", "e8", None),
+        ("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "O-O", Some("e1g1")),
+        ("r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1", "0-0-0", Some("e8c8")),
+        ("rnbqkbnr/pppppppp/8/8/8/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1", "Nbd2", Some("b1d2")),
+        ("rnbqkbnr/pppppppp/8/8/8/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1", "Nd2", None),
+        ("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", "R1a3", Some("a1a3")),
+        ("4k3/8/8/8/7Q/8/8/K6Q w - - 0 1", "Qe1", None),
+        ("4k3/8/8/8/7Q/8/8/K6Q w - - 0 1", "Qh4e1", Some("h4e1")),
+        ("4k3/8/8/8/7Q/8/8/K6Q w - - 0 1", "Q4e1#", Some("h4e1")),
+        ("4k3/8/8/8/7Q/8/8/K6Q w - - 0 1", "Qz4", None)
+    ];
+    for (fen, san, expected) in tests {
+        let board: Board = fen.parse().unwrap();
+        let actual = board.parse_san(san).map(|mv| mv.to_string());
+        assert_eq!(actual.as_deref(), expected, "Parsing '{}' in '{}'", san, fen);
+    }
+}
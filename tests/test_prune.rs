@@ -0,0 +1,83 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::prune::*;
+use std::io::Cursor;
+
+mod common;
+use common::entry;
+
+fn prune_to_vec<I: std::io::Seek + std::io::Read>(
+    reader: &mut PolyglotReader<I>,
+    options: &PruneOptions
+) -> (Vec<u8>, PruneSummary) {
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let summary = prune(reader, &mut writer, options).unwrap();
+    (writer.into_inner(), summary)
+}
+
+#[test]
+fn test_prune_keeps_everything_by_default() {
+    let bytes = std::fs::read("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(Cursor::new(bytes.clone())).unwrap();
+    let (pruned, summary) = prune_to_vec(&mut reader, &PruneOptions::default());
+    assert_eq!(summary.removed_entries(), 0);
+    assert_eq!(summary.kept_entries, reader.len());
+    assert!(pruned == bytes);
+}
+
+#[test]
+fn test_prune_weights() {
+    let mut reader = common::book(&[(1, "e2e4", 90), (1, "d2d4", 8), (1, "c7c5", 2), (2, "e2e4", 1)]);
+
+    let (pruned, summary) = prune_to_vec(&mut reader, &PruneOptions {
+        min_weight: 2,
+        min_weight_share: 0.05,
+        ..PruneOptions::default()
+    });
+    assert_eq!(summary, PruneSummary {
+        kept_entries: 2,
+        kept_positions: 1,
+        removed_positions: 1,
+        removed_by_weight: 1,
+        removed_by_weight_share: 1,
+        removed_unreachable: 0
+    });
+    let mut pruned = PolyglotReader::new(Cursor::new(pruned)).unwrap();
+    let weights: Vec<_> = pruned.get_hash(1).unwrap().iter().map(|entry| entry.weight).collect();
+    assert_eq!(weights, vec![90, 8]);
+    assert!(pruned.get_hash(2).unwrap().is_empty());
+}
+
+#[test]
+fn test_writer_rejects_unsorted_keys() {
+    let mut writer = PolyglotWriter::new(Vec::new());
+    writer.write(2, &entry("e2e4", 1)).unwrap();
+    assert!(writer.write(1, &entry("e2e4", 1)).is_err());
+}
+
+#[test]
+fn test_prune_reachability() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(std::io::BufReader::new(file)).unwrap();
+    let (pruned, summary) = prune_to_vec(&mut reader, &PruneOptions {
+        reachability: Some(Reachability {
+            max_plies: Some(4),
+            ..Reachability::default()
+        }),
+        ..PruneOptions::default()
+    });
+    assert!(summary.removed_unreachable > 0);
+    assert_eq!(summary.kept_entries + summary.removed_entries(), reader.len());
+
+    let mut pruned = PolyglotReader::new(Cursor::new(pruned)).unwrap();
+    assert_eq!(pruned.len(), summary.kept_entries);
+    let lines = walk::reachable_lines(&mut pruned, &[board::Board::default()], Some(4)).unwrap();
+    for position in pruned.positions().unwrap() {
+        let (key, _) = position.unwrap();
+        assert!(lines[&key].ply() <= 4);
+    }
+
+    let deep: PolyglotKey = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3".parse().unwrap();
+    assert!(!pruned.get(&deep).unwrap().is_empty());
+    let deeper: PolyglotKey = "r1bqkbnr/pppp1ppp/2n5/1B2p3/4P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3".parse().unwrap();
+    assert!(pruned.get(&deeper).unwrap().is_empty());
+}
@@ -0,0 +1,122 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::board::Board;
+use chess_polyglot_reader::build::*;
+use chess_polyglot_reader::keys::KeyTable;
+use chess_polyglot_reader::prune::Reachability;
+use chess_polyglot_reader::repertoire::*;
+use std::io::{BufReader, Cursor};
+use std::path::PathBuf;
+
+const REPERTOIRE: &str = "[Event \"Open Sicilian\"]\n\n1. e4 c5 2. Nf3 *\n\n[Event \"Open Games\"]\n\n1. e4 e5 2. a3 *\n";
+
+fn board(moves: &[&str]) -> Board {
+    moves.iter().fold(Board::default(), |board, mv| board.play(mv.parse().unwrap()).unwrap())
+}
+
+fn book() -> PolyglotReader<BufReader<std::fs::File>> {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    PolyglotReader::new(BufReader::new(file)).unwrap()
+}
+
+fn moves<I: std::io::Seek + std::io::Read>(reader: &mut PolyglotReader<I>, board: &Board) -> Vec<(String, u16)> {
+    reader.get(&board.key()).unwrap().iter().map(|entry| (entry.mv.to_string(), entry.weight)).collect()
+}
+
+#[test]
+fn test_repertoire_from_pgn() {
+    let repertoire = Repertoire::from_pgn(Side::White, Cursor::new(REPERTOIRE)).unwrap();
+    assert_eq!(repertoire.side(), Side::White);
+    assert_eq!(repertoire.len(), 3);
+    assert_eq!(repertoire.moves(Board::default().polyglot_hash()), &["e2e4".parse::<Move>().unwrap()]);
+    assert!(repertoire.moves(board(&["e2e4"]).polyglot_hash()).is_empty());
+
+    let black = Repertoire::from_pgn(Side::Black, Cursor::new(REPERTOIRE)).unwrap();
+    assert_eq!(black.moves(board(&["e2e4"]).polyglot_hash()).len(), 2);
+
+    let error = Repertoire::from_pgn(Side::White, Cursor::new("1. e4 e5 2. Ke3 *")).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(error.to_string(), "Game 1: illegal move 'Ke3'");
+}
+
+#[test]
+fn test_repertoire_castling() {
+    let mut repertoire = Repertoire::new(Side::White);
+    let board: Board = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+    assert!(repertoire.add(&board, "e1g1".parse().unwrap()));
+    assert!(repertoire.add(&board, "e1h1".parse().unwrap()));
+    assert!(!repertoire.add(&board, "e1e3".parse().unwrap()));
+    assert!(!repertoire.add(&board.play("e1f1".parse().unwrap()).unwrap(), "e8g8".parse().unwrap()));
+    assert_eq!(repertoire.moves(board.polyglot_hash()), &["e1h1".parse::<Move>().unwrap()]);
+}
+
+#[test]
+fn test_narrow() {
+    let repertoire = Repertoire::from_pgn(Side::White, Cursor::new(REPERTOIRE)).unwrap();
+    let mut reader = book();
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let summary = narrow(&mut reader, &mut writer, &repertoire, &NarrowOptions::default()).unwrap();
+    assert_eq!(summary.added_entries, 0);
+    assert_eq!(summary.removed_unreachable, 0);
+    assert_eq!(summary.kept_entries + summary.removed_entries, reader.len());
+
+    let mut narrowed = PolyglotReader::new(Cursor::new(writer.into_inner())).unwrap();
+    let start = Board::default();
+    let e4 = moves(&mut reader, &start).into_iter().find(|(mv, _)| mv == "e2e4").unwrap();
+    assert_eq!(moves(&mut narrowed, &start), vec![e4]);
+    for line in [&["e2e4"][..], &["d2d4"], &["e2e4", "c7c5"][..]] {
+        let board = board(line);
+        let expected = moves(&mut reader, &board);
+        let actual = moves(&mut narrowed, &board);
+        if board.turn() == Side::Black {
+            assert_eq!(actual, expected);
+        } else {
+            assert_eq!(actual.len(), 1);
+            assert_eq!(actual[0].0, "g1f3");
+        }
+    }
+    assert!(moves(&mut narrowed, &board(&["e2e4", "e7e5"])).is_empty());
+}
+
+#[test]
+fn test_narrow_missing_and_unreachable() {
+    let repertoire = Repertoire::from_pgn(Side::White, Cursor::new(REPERTOIRE)).unwrap();
+    let mut reader = book();
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let summary = narrow(&mut reader, &mut writer, &repertoire, &NarrowOptions {
+        missing_weight: Some(7),
+        reachability: Some(Reachability::default())
+    }).unwrap();
+    assert_eq!(summary.added_entries, 1);
+    assert!(summary.removed_unreachable > 0);
+
+    let mut narrowed = PolyglotReader::new(Cursor::new(writer.into_inner())).unwrap();
+    assert_eq!(narrowed.len(), summary.kept_entries);
+    assert_eq!(moves(&mut narrowed, &board(&["e2e4", "e7e5"])), vec![("a2a3".to_string(), 7)]);
+    assert!(moves(&mut narrowed, &board(&["d2d4"])).is_empty());
+    assert_eq!(moves(&mut narrowed, &board(&["e2e4"])), moves(&mut reader, &board(&["e2e4"])));
+    assert!(!moves(&mut narrowed, &board(&["e2e4", "c7c5", "g1f3"])).is_empty());
+}
+
+#[test]
+fn test_narrow_custom_table() {
+    let table = KeyTable::from_seed(42);
+    let paths = [PathBuf::from("test-data/games_a.pgn"), PathBuf::from("test-data/games_b.pgn")];
+    let mut writer = PolyglotWriter::new(Vec::new());
+    build_book(&paths, &mut writer, &BuildOptions {
+        table: table.clone(),
+        ..BuildOptions::default()
+    }).unwrap();
+
+    let repertoire = Repertoire::from_pgn(Side::White, Cursor::new(REPERTOIRE)).unwrap();
+    let mut reader = PolyglotReader::with_table(Cursor::new(writer.into_inner()), table.clone()).unwrap();
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let summary = narrow(&mut reader, &mut writer, &repertoire, &NarrowOptions::default()).unwrap();
+    assert!(summary.narrowed_positions > 0);
+    assert!(summary.removed_entries > 0);
+
+    let mut narrowed = PolyglotReader::with_table(Cursor::new(writer.into_inner()), table).unwrap();
+    assert_eq!(moves(&mut narrowed, &Board::default()).len(), 1);
+    assert_eq!(moves(&mut narrowed, &Board::default())[0].0, "e2e4");
+    assert!(moves(&mut narrowed, &board(&["e2e4", "e7e5"])).is_empty());
+    assert_eq!(moves(&mut narrowed, &board(&["e2e4"])), moves(&mut reader, &board(&["e2e4"])));
+}
@@ -0,0 +1,61 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::replay::*;
+use std::io::BufReader;
+
+const RUY_LOPEZ: &[&str] = &["e2e4", "e7e5", "g1f3", "b8c6", "f1b5", "a7a6", "b5a4", "g8f6", "e1g1"];
+
+fn reader() -> PolyglotReader<BufReader<std::fs::File>> {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    PolyglotReader::new(BufReader::new(file)).unwrap()
+}
+
+#[test]
+fn test_replay_in_book() {
+    let mut reader = reader();
+    let positions = replay(&mut reader, None, RUY_LOPEZ, &ReplayOptions::default()).unwrap();
+    assert_eq!(positions.len(), RUY_LOPEZ.len() + 1);
+    assert_eq!(positions[0].hash, 0x463b96181691fc9c);
+    assert_eq!(positions[0].played, Some("e2e4".parse().unwrap()));
+    for (ply, position) in positions.iter().enumerate().take(RUY_LOPEZ.len()) {
+        assert_eq!(position.ply, ply);
+        assert!(position.book_move, "Ply {}", ply);
+    }
+    assert_eq!(positions[8].played, Some("e1h1".parse().unwrap()));
+    assert_eq!(positions[9].played, None);
+    assert!(!positions[9].book_move);
+
+    let polyglot_castling: Vec<_> = RUY_LOPEZ.iter().map(|&uci| if uci == "e1g1" { "e1h1" } else { uci }).collect();
+    let again = replay(&mut reader, None, &polyglot_castling, &ReplayOptions::default()).unwrap();
+    assert_eq!(again.last().unwrap().hash, positions.last().unwrap().hash);
+}
+
+#[test]
+fn test_replay_out_of_book() {
+    let mut reader = reader();
+    let moves = ["e2e4", "e7e5", "a2a3", "h7h6", "h2h3"];
+    let positions = replay(&mut reader, None, moves, &ReplayOptions::default()).unwrap();
+    assert_eq!(positions.len(), 6);
+    assert!(positions[1].book_move);
+    assert!(!positions[2].book_move);
+    assert!(!positions[2].entries.is_empty());
+    assert!(positions[4].entries.is_empty());
+
+    let stopped = replay(&mut reader, None, moves, &ReplayOptions {
+        stop_out_of_book: true
+    }).unwrap();
+    assert_eq!(stopped.len(), 3);
+    assert_eq!(stopped[2].played, Some("a2a3".parse().unwrap()));
+}
+
+#[test]
+fn test_replay_from_fen() {
+    let mut reader = reader();
+    let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+    let positions = replay(&mut reader, Some(fen), ["c7c5"], &ReplayOptions::default()).unwrap();
+    assert_eq!(positions[0].hash, fen.parse::<PolyglotKey>().unwrap().polyglot_hash());
+    assert!(positions[0].book_move);
+
+    assert!(replay(&mut reader, Some("not a fen"), ["e2e4"], &ReplayOptions::default()).is_err());
+    assert!(replay(&mut reader, None, ["e2e5"], &ReplayOptions::default()).is_err());
+    assert!(replay(&mut reader, None, ["e2"], &ReplayOptions::default()).is_err());
+}
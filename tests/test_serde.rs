@@ -0,0 +1,89 @@
+use chess_polyglot_reader::*;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+struct Lookup {
+    #[serde(with = "chess_polyglot_reader::serde_hex")]
+    hash: u64
+}
+
+#[test]
+fn test_serde_moves() {
+    let entry = PolyglotEntry {
+        mv: "e7e8q".parse().unwrap(),
+        weight: 100,
+        learn: 7
+    };
+    let json = serde_json::to_string(&entry).unwrap();
+    assert_eq!(json, r#"{"mv":"e7e8q","weight":100,"learn":7}"#);
+    let parsed: PolyglotEntry = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.mv, entry.mv);
+    assert_eq!((parsed.weight, parsed.learn), (100, 7));
+
+    assert_eq!(serde_json::to_string(&Square::new(3, 4).unwrap()).unwrap(), r#""e4""#);
+    assert_eq!(serde_json::to_string(&Side::Black).unwrap(), r#""Black""#);
+    assert_eq!(serde_json::to_string(&PieceType::Knight).unwrap(), r#""Knight""#);
+    assert!(serde_json::from_str::<Move>(r#""e7e8k""#).is_err());
+    assert!(serde_json::from_str::<Square>(r#""i9""#).is_err());
+}
+
+#[test]
+fn test_serde_hashes() {
+    let lookup = Lookup { hash: 0x463b96181691fc9c };
+    let json = serde_json::to_string(&lookup).unwrap();
+    assert_eq!(json, r#"{"hash":"463b96181691fc9c"}"#);
+    assert_eq!(serde_json::from_str::<Lookup>(&json).unwrap(), lookup);
+    assert_eq!(serde_json::from_str::<Lookup>(r#"{"hash":"0x1"}"#).unwrap(), Lookup { hash: 1 });
+    assert!(serde_json::from_str::<Lookup>(r#"{"hash":"g"}"#).is_err());
+    assert!(serde_json::from_str::<Lookup>(r#"{"hash":"+1"}"#).is_err());
+    assert!(serde_json::from_str::<Lookup>(r#"{"hash":12}"#).is_err());
+}
+
+#[test]
+fn test_serde_keys() {
+    let key: PolyglotKey = "4k3/8/8/8/8/8/8/4K2R w K - 0 1".parse().unwrap();
+    let json = serde_json::to_value(&key).unwrap();
+    assert_eq!(json, serde_json::json!({
+        "pieces": [
+            { "piece_type": "King", "side": "Black", "square": "e8" },
+            { "piece_type": "King", "side": "White", "square": "e1" },
+            { "piece_type": "Rook", "side": "White", "square": "h1" }
+        ],
+        "white_castle": { "queen_side": false, "king_side": true },
+        "black_castle": { "queen_side": false, "king_side": false },
+        "en_passant_file": null,
+        "turn": "White",
+        "hash": format!("{:016x}", key.polyglot_hash())
+    }));
+
+    let parsed: PolyglotKey = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(parsed.polyglot_hash(), key.polyglot_hash());
+
+    let mut without_hash = json.clone();
+    without_hash.as_object_mut().unwrap().remove("hash");
+    let parsed: PolyglotKey = serde_json::from_value(without_hash).unwrap();
+    assert_eq!(parsed.polyglot_hash(), key.polyglot_hash());
+
+    let mut wrong_hash = json.clone();
+    wrong_hash["hash"] = "0000000000000000".into();
+    assert!(serde_json::from_value::<PolyglotKey>(wrong_hash).is_err());
+
+    let mut wrong_file = json;
+    wrong_file["en_passant_file"] = 8.into();
+    wrong_file.as_object_mut().unwrap().remove("hash");
+    assert!(serde_json::from_value::<PolyglotKey>(wrong_file).is_err());
+}
+
+#[test]
+fn test_serde_variant_keys() {
+    let key: PolyglotKey = "4k3/8/8/8/8/8/8/4K3[Qp] w - - 0 1".parse().unwrap();
+    let json = serde_json::to_value(&key).unwrap();
+    assert_eq!(json["variant"], serde_json::json!({
+        "Crazyhouse": {
+            "pockets": { "white": [0, 0, 0, 0, 1], "black": [1, 0, 0, 0, 0] },
+            "promoted": 0
+        }
+    }));
+    let parsed: PolyglotKey = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.variant, key.variant);
+    assert_eq!(parsed.polyglot_hash(), key.polyglot_hash());
+}
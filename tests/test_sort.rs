@@ -0,0 +1,91 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::sort::*;
+use std::io::Cursor;
+
+fn entry(mv: u16, weight: u16, learn: u32) -> PolyglotEntry {
+    PolyglotEntry {
+        mv: Move::from_u16(mv),
+        weight,
+        learn
+    }
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("chess-polyglot-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn read_all(bytes: Vec<u8>) -> Vec<(u64, u16, u16, u32)> {
+    let mut reader = PolyglotReader::new(Cursor::new(bytes)).unwrap();
+    reader.entries().unwrap().map(|entry| {
+        let (key, entry) = entry.unwrap();
+        (key, entry.mv.to_u16(), entry.weight, entry.learn)
+    }).collect()
+}
+
+#[test]
+fn test_sort_in_memory() {
+    let mut sorter = ExternalSorter::new(SortOptions::default());
+    sorter.push(2, entry(0x031c, 1, 0)).unwrap();
+    sorter.push(1, entry(0x02db, 3, 0)).unwrap();
+    sorter.push(1, entry(0x031c, 2, 1)).unwrap();
+    sorter.push(1, entry(0x031c, 2, 1)).unwrap();
+    assert_eq!(sorter.runs(), 0);
+    let mut writer = PolyglotWriter::new(Vec::new());
+    assert_eq!(sorter.finish(&mut writer).unwrap(), 3);
+    assert_eq!(read_all(writer.into_inner()), vec![
+        (1, 0x031c, 4, 0),
+        (1, 0x02db, 3, 0),
+        (2, 0x031c, 1, 0)
+    ]);
+}
+
+#[test]
+fn test_sort_spills_runs() {
+    let dir = temp_dir("sort");
+    let mut sorter = ExternalSorter::new(SortOptions {
+        memory_limit: 100,
+        temp_dir: dir.clone()
+    });
+    let moves = [0x031c, 0x02db, 0x0ca2, 0x0195];
+    let mut expected = std::collections::BTreeMap::new();
+    for i in 0..500u64 {
+        let key = (i * 7919) % 61;
+        let mv = moves[(i % 4) as usize];
+        let weight = (i % 5) as u16 * 1000;
+        sorter.push(key, entry(mv, weight, 0)).unwrap();
+        *expected.entry((key, mv)).or_insert(0u16) = expected.get(&(key, mv)).copied().unwrap_or(0).saturating_add(weight);
+    }
+    assert!(sorter.runs() > 10);
+    assert!(std::fs::read_dir(&dir).unwrap().count() > 10);
+
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let written = sorter.finish(&mut writer).unwrap();
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    std::fs::remove_dir(&dir).unwrap();
+
+    let entries = read_all(writer.into_inner());
+    assert_eq!(written, expected.len());
+    assert_eq!(entries.len(), expected.len());
+    for window in entries.windows(2) {
+        assert!(window[0].0 < window[1].0 || (window[0].0 == window[1].0 && window[0].2 >= window[1].2));
+    }
+    for (key, mv, weight, _) in entries {
+        assert_eq!(expected[&(key, mv)], weight);
+    }
+}
+
+#[test]
+fn test_sort_removes_runs_when_dropped() {
+    let dir = temp_dir("drop");
+    let mut sorter = ExternalSorter::new(SortOptions {
+        memory_limit: 1,
+        temp_dir: dir.clone()
+    });
+    sorter.push(1, entry(0x031c, 1, 0)).unwrap();
+    assert_eq!(sorter.runs(), 1);
+    drop(sorter);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    std::fs::remove_dir(&dir).unwrap();
+}
@@ -0,0 +1,97 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::build::*;
+use chess_polyglot_reader::keys::KeyTable;
+use chess_polyglot_reader::stats::*;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+mod common;
+
+const START: u64 = 0x463b96181691fc9c;
+
+fn book_and_sidecar() -> BookStats<Cursor<Vec<u8>>, Cursor<Vec<u8>>> {
+    let paths = [PathBuf::from("test-data/games_a.pgn"), PathBuf::from("test-data/games_b.pgn")];
+    let (stats, _) = collect_stats(&paths, &BuildOptions::default()).unwrap();
+    let mut book = PolyglotWriter::new(Vec::new());
+    write_stats(&stats, &mut book).unwrap();
+    let mut sidecar = StatsWriter::new(Vec::new());
+    write_sidecar(&stats, &mut sidecar).unwrap();
+    assert_eq!(sidecar.len(), stats.len());
+    BookStats::new(
+        PolyglotReader::new(Cursor::new(book.into_inner())).unwrap(),
+        StatsReader::new(Cursor::new(sidecar.into_inner())).unwrap()
+    )
+}
+
+#[test]
+fn test_join() {
+    let mut book = book_and_sidecar();
+    let start: Vec<_> = book.get_hash(START).unwrap().iter().map(ToString::to_string).collect();
+    assert_eq!(start, vec![
+        "e2e4 (3): 3 games, +1 =1 -1, 50.0%",
+        "d2d4 (0): 1 game, +0 =0 -1, 0.0%"
+    ]);
+    let castling: PolyglotKey = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1".parse().unwrap();
+    let entries = book.get(&castling).unwrap();
+    assert_eq!(entries[0].to_string(), "e1h1 (0): 1 game, +0 =0 -0");
+    assert_eq!(entries[0].stats.unwrap().score(), None);
+    assert!(book.get_hash(0).unwrap().is_empty());
+
+    for (key, entry) in book.book.entries().unwrap().map(Result::unwrap).collect::<Vec<_>>() {
+        let stats = book.stats.get_hash(key).unwrap();
+        let (_, move_stats) = stats.iter().find(|(mv, _)| *mv == entry.mv).unwrap();
+        assert_eq!(move_stats.weight(), entry.weight as u64);
+    }
+}
+
+#[test]
+fn test_missing_stats() {
+    let mut sidecar = StatsWriter::new(Vec::new());
+    sidecar.write(START, "e2e4".parse().unwrap(), &MoveStats {
+        games: 4,
+        wins: 3,
+        draws: 0,
+        losses: 1
+    }).unwrap();
+    let mut book = BookStats::new(
+        common::book(&[(START, "e2e4", 2), (START, "d2d4", 1)]),
+        StatsReader::new(Cursor::new(sidecar.into_inner())).unwrap()
+    );
+    let start: Vec<_> = book.get_hash(START).unwrap().iter().map(ToString::to_string).collect();
+    assert_eq!(start, vec!["e2e4 (2): 4 games, +3 =0 -1, 75.0%", "d2d4 (1)"]);
+}
+
+#[test]
+fn test_sidecar_errors() {
+    let mut sidecar = StatsWriter::new(Vec::new());
+    let stats = MoveStats::default();
+    sidecar.write(START, "e2e4".parse().unwrap(), &stats).unwrap();
+    let error = sidecar.write(START, "e2e4".parse().unwrap(), &stats).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(sidecar.write(START, "d2d4".parse().unwrap(), &stats).is_err());
+    assert!(sidecar.write(START + 1, "d2d4".parse().unwrap(), &stats).is_ok());
+
+    let error = StatsReader::new(Cursor::new(vec![0; 27])).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(sidecar_path(Path::new("books/main.bin")), Path::new("books/main.stats"));
+}
+
+#[test]
+fn test_sidecar_custom_table() {
+    let table = KeyTable::from_seed(42);
+    let start: PolyglotKey = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse().unwrap();
+    let mut sidecar = StatsWriter::new(Vec::new());
+    sidecar.write(start.polyglot_hash_with(&table), "e2e4".parse().unwrap(), &MoveStats {
+        games: 1,
+        wins: 1,
+        draws: 0,
+        losses: 0
+    }).unwrap();
+    let sidecar = sidecar.into_inner();
+
+    let mut standard = StatsReader::new(Cursor::new(sidecar.clone())).unwrap();
+    assert!(standard.get(&start).unwrap().is_empty());
+    let mut reader = StatsReader::with_table(Cursor::new(sidecar), table.clone()).unwrap();
+    assert_eq!(reader.table(), &table);
+    assert_eq!(reader.get(&start).unwrap().len(), 1);
+}
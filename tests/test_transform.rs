@@ -0,0 +1,144 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::board::Board;
+use chess_polyglot_reader::transform::*;
+use std::io::{BufReader, Cursor};
+
+const MIRRORED_START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1";
+
+const TESTS: &[(&str, &str)] = &[
+    (
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+        "rnbqkbnr/pppp1ppp/8/4p3/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    ),
+    (
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "rnbqkbnr/pppp1ppp/8/8/3PpP2/8/PPP1P1PP/RNBQKBNR b KQkq f3 0 3"
+    ),
+    (
+        "rnbq1bnr/ppp1pkpp/8/3pPp2/8/8/PPPPKPPP/RNBQ1BNR w - - 0 4",
+        "rnbq1bnr/ppppkppp/8/8/3PpP2/8/PPP1PKPP/RNBQ1BNR b - - 0 4"
+    ),
+    (
+        "r3k2r/8/8/8/8/8/8/4K3 w kq - 0 1",
+        "4k3/8/8/8/8/8/8/R3K2R b KQ - 0 1"
+    )
+];
+
+#[test]
+fn test_mirrored_keys() {
+    for (i, &(fen, mirrored_fen)) in TESTS.iter().enumerate() {
+        let key: PolyglotKey = fen.parse().unwrap();
+        let expected: PolyglotKey = mirrored_fen.parse().unwrap();
+        assert_eq!(key.mirrored().polyglot_hash(), expected.polyglot_hash(), "Testing mirror of '{}' (Test {})", fen, i + 1);
+    }
+}
+
+#[test]
+fn test_mirrored_moves() {
+    let castle = Move::from_u16(0x0107);
+    assert_eq!(castle.mirrored(), Move::from_u16(0x0f3f));
+    assert_eq!(castle.mirrored().mirrored(), castle);
+}
+
+#[test]
+fn test_mirror_book() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(BufReader::new(file)).unwrap();
+
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let summary = mirror(&mut reader, &mut writer, &[Board::default()]).unwrap();
+    assert!(summary.mirrored_positions > 0);
+    assert_eq!(summary.mirrored_entries + summary.skipped_entries, reader.len());
+
+    let mut mirrored = PolyglotReader::new(Cursor::new(writer.into_inner())).unwrap();
+    assert_eq!(mirrored.len(), summary.mirrored_entries);
+    let mut restored = PolyglotWriter::new(Vec::new());
+    let mirrored_root: Board = MIRRORED_START.parse().unwrap();
+    let restored_summary = mirror(&mut mirrored, &mut restored, &[mirrored_root]).unwrap();
+    assert_eq!(restored_summary, MirrorSummary {
+        mirrored_positions: summary.mirrored_positions,
+        mirrored_entries: summary.mirrored_entries,
+        skipped_positions: 0,
+        skipped_entries: 0
+    });
+
+    let mut restored = PolyglotReader::new(Cursor::new(restored.into_inner())).unwrap();
+    let start = Board::default().key();
+    let mut expected: Vec<_> = reader.get(&start).unwrap().iter().map(|entry| (entry.mv.to_u16(), entry.weight)).collect();
+    let mut actual: Vec<_> = restored.get(&start).unwrap().iter().map(|entry| (entry.mv.to_u16(), entry.weight)).collect();
+    expected.sort_unstable();
+    actual.sort_unstable();
+    assert_eq!(actual, expected);
+}
+
+fn rescaled(weights: &[u16], rescale: Rescale) -> Vec<u16> {
+    let mut entries: Vec<_> = weights.iter().enumerate().map(|(i, &weight)| PolyglotEntry {
+        mv: Move::from_u16(i as u16),
+        weight,
+        learn: 0
+    }).collect();
+    rescale.apply(&mut entries).unwrap();
+    entries.iter().map(|entry| entry.weight).collect()
+}
+
+#[test]
+fn test_rescale_weights() {
+    assert_eq!(rescaled(&[65535, 65535, 65535], Rescale::SumTo(1000)), vec![334, 333, 333]);
+    assert_eq!(rescaled(&[6, 3, 1], Rescale::SumTo(20)), vec![12, 6, 2]);
+    assert_eq!(rescaled(&[0, 0], Rescale::SumTo(100)), vec![0, 0]);
+    assert_eq!(rescaled(&[2, 1, 1, 1], Rescale::Percentages), vec![40, 20, 20, 20]);
+    assert_eq!(rescaled(&[1, 1, 1], Rescale::Percentages), vec![34, 33, 33]);
+    assert_eq!(rescaled(&[0, 1, 65535], Rescale::Log { scale: 100.0 }), vec![0, 69, 1109]);
+    assert_eq!(rescaled(&[0, 50, 65535], Rescale::Clamp { min: 1, max: 1000 }), vec![1, 50, 1000]);
+    assert_eq!(rescaled(&[10, 60, 30], Rescale::BestMoveShare { share: 0.8 }), vec![5, 80, 15]);
+    assert_eq!(rescaled(&[100, 0, 0], Rescale::BestMoveShare { share: 0.5 }), vec![50, 25, 25]);
+    assert_eq!(rescaled(&[7], Rescale::BestMoveShare { share: 0.5 }), vec![7]);
+    assert_eq!(rescaled(&[65535, 65535, 65535, 65535], Rescale::BestMoveShare { share: 0.5 }), vec![32768, 10923, 10922, 10922]);
+
+    let mut entries = vec![PolyglotEntry {
+        mv: Move::from_u16(0),
+        weight: 50,
+        learn: 0
+    }];
+    let error = Rescale::Clamp { min: 1000, max: 1 }.apply(&mut entries).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert_eq!(entries[0].weight, 50);
+}
+
+#[test]
+fn test_rescale_book() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(BufReader::new(file)).unwrap();
+
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let summary = rescale(&mut reader, &mut writer, &[Rescale::Clamp { min: 1, max: u16::MAX }, Rescale::SumTo(1000)]).unwrap();
+    assert_eq!(summary.entries, reader.len());
+    assert!(summary.changed_entries > 0);
+
+    let mut rescaled = PolyglotReader::new(Cursor::new(writer.into_inner())).unwrap();
+    assert_eq!(rescaled.len(), reader.len());
+    let mut positions = 0;
+    for position in rescaled.positions().unwrap() {
+        let (_, entries) = position.unwrap();
+        positions += 1;
+        assert_eq!(entries.iter().map(|entry| entry.weight as u32).sum::<u32>(), 1000);
+        assert!(entries.windows(2).all(|pair| pair[0].weight >= pair[1].weight));
+    }
+    assert_eq!(positions, summary.positions);
+
+    let start = Board::default().key();
+    let original: Vec<_> = reader.get(&start).unwrap().iter().map(|entry| entry.mv).collect();
+    let moves: Vec<_> = rescaled.get(&start).unwrap().iter().map(|entry| entry.mv).collect();
+    assert_eq!(moves, original);
+}
+
+#[test]
+fn test_rescale_invalid_clamp() {
+    let file = std::fs::File::open("test-data/test_book.bin").unwrap();
+    let mut reader = PolyglotReader::new(BufReader::new(file)).unwrap();
+
+    let mut writer = PolyglotWriter::new(Vec::new());
+    let error = rescale(&mut reader, &mut writer, &[Rescale::SumTo(1000), Rescale::Clamp { min: 10, max: 5 }]).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(writer.into_inner().is_empty());
+}
@@ -0,0 +1,104 @@
+use chess_polyglot_reader::*;
+use chess_polyglot_reader::board::Board;
+use chess_polyglot_reader::variant::*;
+use std::collections::HashSet;
+
+const START: u64 = 0x463b96181691fc9c;
+
+fn hash(fen: &str) -> u64 {
+    fen.parse::<PolyglotKey>().unwrap().polyglot_hash()
+}
+
+#[test]
+fn test_standard_hashes_unchanged() {
+    let key: PolyglotKey = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".parse().unwrap();
+    assert_eq!(key.variant, None);
+    assert_eq!(key.polyglot_hash(), START);
+    assert_eq!(hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1"), START);
+    assert_eq!(hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 3+3 0 1"), START);
+    assert_eq!(hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +0+0"), START);
+
+    assert_eq!(hash("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1"), START);
+
+    let randoms: HashSet<_> = RANDOM_POCKET.iter().chain(&RANDOM_PROMOTED).chain(&RANDOM_CHECKS).collect();
+    assert_eq!(randoms.len(), RANDOM_POCKET.len() + RANDOM_PROMOTED.len() + RANDOM_CHECKS.len());
+    assert!(!randoms.contains(&0));
+}
+
+/// Hashes computed by shakmaty 0.30's `Zobrist64` with `EnPassantMode::Legal`.
+#[test]
+fn test_shakmaty_hashes() {
+    let tests = [
+        ("r1bqk2r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQK2R[Bb] w KQkq - 0 6", 0xc91bc91533b9bc49),
+        ("4k3/8/8/8/8/8/8/4K3[Qp] w - - 0 1", 0x0aaa19c40c25df2c),
+        ("4k3/8/8/8/8/8/8/4K3[PPPPPPPPPPPPPPPPnnnn] w - - 0 1", 0x992f5cc2e9031bd2),
+        ("4k3/8/8/8/8/8/8/3Q~K3[] w - - 0 1", 0xfb68dfb9a94bc9a1),
+        ("4k3/8/8/8/8/8/8/3QK3[] w - - 0 1", 0x5658d6a54e808ba5),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 2+3 0 1", 0xdeba12ca45126b68),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +1+0", 0xdeba12ca45126b68),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +2+3", 0x34430a382c20847f),
+        ("rnbqkb1r/pppp1ppp/5n2/4p3/2B1P3/8/PPPP1PPP/RNBQK1NR w KQkq - 1+2 2 3", 0x9cf71fe06dbe5743)
+    ];
+    for (fen, expected) in tests {
+        assert_eq!(hash(fen), expected, "Testing hash for '{}'", fen);
+    }
+}
+
+#[test]
+fn test_crazyhouse_keys() {
+    let key: PolyglotKey = "r1bqk2r/pppp1ppp/2n2n2/4p3/4P3/2N2N2/PPPP1PPP/R1BQK2R[Bbp] w KQkq - 0 6".parse().unwrap();
+    let pockets = match key.variant {
+        Some(VariantState::Crazyhouse { pockets, promoted: 0 }) => pockets,
+        _ => panic!("Expected Crazyhouse pockets")
+    };
+    assert_eq!(pockets.count(Side::White, PieceType::Bishop), 1);
+    assert_eq!(pockets.count(Side::Black, PieceType::Bishop), 1);
+    assert_eq!(pockets.count(Side::Black, PieceType::Pawn), 1);
+    assert_eq!(pockets.count(Side::White, PieceType::King), 0);
+
+    let fen = |pockets: &str| format!("4k3/8/8/8/8/8/8/4K3[{}] w - - 0 1", pockets);
+    let empty = hash(&fen(""));
+    assert_eq!(hash(&fen("Qp")), hash(&fen("pQ")));
+    assert_ne!(hash(&fen("Q")), empty);
+    assert_ne!(hash(&fen("Q")), hash(&fen("q")));
+    assert_ne!(hash(&fen("PP")), hash(&fen("P")));
+    assert_ne!(hash(&fen("PP")), empty);
+    assert_ne!(hash("4k3/8/8/8/8/8/8/3QK3[] w - - 0 1"), hash("4k3/8/8/8/8/8/8/3Q~K3[] w - - 0 1"));
+    let promoted: PolyglotKey = "4k3/8/8/8/8/8/8/3Q~K3[] w - - 0 1".parse().unwrap();
+    assert_eq!(promoted.variant, Some(VariantState::Crazyhouse {
+        pockets: Pockets::default(),
+        promoted: 1 << 3
+    }));
+    assert!("4k3/8/8/8/8/8/8/3Q~K3 w - - 0 1".parse::<PolyglotKey>().is_err());
+    assert!("4k3/8/8/8/8/8/8/~3QK3[] w - - 0 1".parse::<PolyglotKey>().is_err());
+    assert!("4k3/8/8/8/8/8/8/4K3[K] w - - 0 1".parse::<PolyglotKey>().is_err());
+    assert!("4k3/8/8/8/8/8/8/4K3[Q w - - 0 1".parse::<PolyglotKey>().is_err());
+    assert!(fen("Q").parse::<Board>().is_err());
+}
+
+#[test]
+fn test_three_check_keys() {
+    let key: PolyglotKey = "4k3/8/8/8/8/8/8/4K3 w - - 2+3 0 1".parse().unwrap();
+    assert_eq!(key.variant, Some(VariantState::ThreeCheck {
+        white_checks: 1,
+        black_checks: 0
+    }));
+    assert_eq!(key.polyglot_hash(), hash("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +1+0"));
+    assert_ne!(key.polyglot_hash(), hash("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +0+1"));
+    assert_ne!(key.polyglot_hash(), hash("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +2+0"));
+    assert!("4k3/8/8/8/8/8/8/4K3 w - - 4+3 0 1".parse::<PolyglotKey>().is_err());
+    assert!("4k3/8/8/8/8/8/8/4K3[] w - - 3+3 0 1".parse::<PolyglotKey>().is_err());
+}
+
+#[test]
+fn test_mirrored_variant_keys() {
+    let tests = [
+        ("4k3/8/8/8/8/8/8/4K3[Qn] w - - 0 1", "4k3/8/8/8/8/8/8/4K3[qN] b - - 0 1"),
+        ("4k3/8/8/8/8/8/8/3Q~K3[] w - - 0 1", "3q~k3/8/8/8/8/8/8/4K3[] b - - 0 1"),
+        ("4k3/8/8/8/8/8/8/4K3 w - - 0 1 +2+1", "4k3/8/8/8/8/8/8/4K3 b - - 0 1 +1+2")
+    ];
+    for (fen, mirrored_fen) in tests {
+        let key: PolyglotKey = fen.parse().unwrap();
+        assert_eq!(key.mirrored().polyglot_hash(), hash(mirrored_fen), "Testing mirror of '{}'", fen);
+    }
+}
@@ -0,0 +1,20 @@
+use chess_polyglot_reader::wasm::*;
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+#[test]
+fn test_wasm_book() {
+    let bytes = std::fs::read("test-data/test_book.bin").unwrap();
+    let mut book = Book::new(bytes).unwrap_or_else(|_| panic!("Failed to read book"));
+    assert!(book.length() > 0);
+    assert_eq!(hash(START).unwrap_or_else(|_| panic!("Failed to hash")), "463b96181691fc9c");
+
+    let moves = book.probe(START).unwrap_or_else(|_| panic!("Failed to probe"));
+    let mut ucis: Vec<_> = moves.iter().map(|mv| mv.uci.clone()).collect();
+    ucis.sort();
+    assert_eq!(ucis, vec!["c2c4", "d2d4", "e2e4"]);
+
+    let by_hash = book.probe_hash("463b96181691fc9c").unwrap_or_else(|_| panic!("Failed to probe hash"));
+    let weights: Vec<_> = by_hash.iter().map(|mv| (mv.weight, mv.learn)).collect();
+    assert_eq!(weights, moves.iter().map(|mv| (mv.weight, mv.learn)).collect::<Vec<_>>());
+}